@@ -17,6 +17,8 @@ impl Component for App {
     fn update(&mut self, message: AppMessage) -> UpdateAction<Self> {
         match message {
             AppMessage::Exit => {
+                // do not leave a secret behind on the clipboard
+                self.password_list_model.restore_clipboard();
                 vgtk::quit();
                 UpdateAction::None
             },
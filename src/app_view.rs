@@ -14,10 +14,44 @@ impl View for App {
                     Application::accels=["F5", "<Ctrl>R"].as_ref()
                     on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::Reload)
                 />
+                // no bare BackSpace, as accelerators take precedence over the focused text entry
                 <SimpleAction::new("go-back", None)
-                    Application::accels=["BackSpace", "<Alt>Left"].as_ref()
+                    Application::accels=["<Alt>Left"].as_ref()
                     on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::Back)
                 />
+                <SimpleAction::new("new-entry", None)
+                    Application::accels=["<Ctrl>N"].as_ref()
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::NewEntry)
+                />
+                <SimpleAction::new("sync", None)
+                    Application::accels=["<Ctrl><Shift>R"].as_ref()
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::Sync)
+                />
+                <SimpleAction::new("pull", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::Pull)
+                />
+                <SimpleAction::new("push", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::Push)
+                />
+                <SimpleAction::new("sync-tick", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::SyncTick)
+                />
+                <SimpleAction::new("reencryption-step", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::ReencryptionStep)
+                />
+                <SimpleAction::new("clipboard-tick", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::ClipboardTick)
+                />
+                <SimpleAction::new("otp-tick", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::OtpTick)
+                />
+                <SimpleAction::new("store-settings", None)
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::ShowStoreSettings)
+                />
+                <SimpleAction::new("preferences", None)
+                    Application::accels=["<Ctrl>comma"].as_ref()
+                    on activate=|_, _| AppMessage::PasswordList(PasswordListMessage::ShowPreferences)
+                />
 
                 <Window
                     default_height=400
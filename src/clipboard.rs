@@ -0,0 +1,71 @@
+use vgtk::lib::gdk;
+use vgtk::lib::gtk::{Clipboard, SelectionData, TargetEntry, TargetFlags};
+
+use std::env;
+
+const DEFAULT_CLIP_TIME: u64 = 45;
+
+/// The target clipboard managers like Klipper and GPaste check to keep secrets out of their
+/// history.
+const PASSWORD_MANAGER_HINT: &str = "x-kde-passwordManagerHint";
+
+const TEXT_TARGETS: &[&str] = &["UTF8_STRING", "text/plain;charset=utf-8", "text/plain", "STRING", "TEXT"];
+const TEXT_INFO: u32 = 0;
+const HINT_INFO: u32 = 1;
+
+/// For how many seconds copied secrets stay on the clipboard, read from the same environment
+/// variable `pass` uses.
+pub fn clip_time() -> u64 {
+    env::var("PASSWORD_STORE_CLIP_TIME")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .filter(|&seconds| seconds > 0)
+        .unwrap_or(DEFAULT_CLIP_TIME)
+}
+
+/// The X selection to copy to, `clipboard` unless `PASSWORD_STORE_X_SELECTION` says otherwise.
+fn clipboard() -> Clipboard {
+    let selection = match env::var("PASSWORD_STORE_X_SELECTION").as_ref().map(|name| name.as_str()) {
+        Ok("primary") | Ok("p") => &gdk::SELECTION_PRIMARY,
+        Ok("secondary") | Ok("s") => &gdk::SELECTION_SECONDARY,
+        _ => &gdk::SELECTION_CLIPBOARD,
+    };
+
+    Clipboard::get(selection)
+}
+
+/// The current text content of the clipboard.
+pub fn text() -> Option<String> {
+    clipboard().wait_for_text().map(|text| text.to_string())
+}
+
+/// Whether the current clipboard content is marked as secret by a password manager.
+pub fn is_secret() -> bool {
+    clipboard().wait_is_target_available(&gdk::Atom::intern(PASSWORD_MANAGER_HINT))
+}
+
+pub fn set_text(text: &str) {
+    clipboard().set_text(text);
+}
+
+/// Copy a secret, marked with the password manager hint so clipboard managers do not store it.
+pub fn set_secret(secret: &str) {
+    let mut targets = TEXT_TARGETS
+        .iter()
+        .map(|target| TargetEntry::new(target, TargetFlags::empty(), TEXT_INFO))
+        .collect::<Vec<_>>();
+    targets.push(TargetEntry::new(PASSWORD_MANAGER_HINT, TargetFlags::empty(), HINT_INFO));
+
+    let secret = secret.to_owned();
+    clipboard().set_with_data(&targets, move |_, selection_data: &SelectionData, info| {
+        if info == HINT_INFO {
+            selection_data.set(&gdk::Atom::intern(PASSWORD_MANAGER_HINT), 8, b"secret");
+        } else {
+            selection_data.set_text(&secret);
+        }
+    });
+}
+
+pub fn clear() {
+    clipboard().clear();
+}
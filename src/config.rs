@@ -0,0 +1,93 @@
+use crate::crypto::CryptoBackendKind;
+use crate::error::Error;
+
+use directories::ProjectDirs;
+
+use std::{io, fs};
+use std::path::{Path, PathBuf};
+
+/// Persistent application settings, stored as simple `key = value` lines in the user's
+/// configuration directory.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub crypto_backend: CryptoBackendKind,
+    /// The password store chosen during onboarding. `PASSWORD_STORE_DIR` takes precedence.
+    pub store_path: Option<PathBuf>,
+    /// The newest commit of each password store's remote whose history has been verified or
+    /// explicitly trusted by the user.
+    trusted_commits: Vec<(PathBuf, String)>,
+}
+
+impl Config {
+    /// The trusted commit of the password store at `store_path`, if the store was opened before.
+    pub fn trusted_commit(&self, store_path: &Path) -> Option<&str> {
+        self.trusted_commits
+            .iter()
+            .find(|(path, _)| path == store_path)
+            .map(|(_, commit)| commit.as_str())
+    }
+
+    pub fn set_trusted_commit(&mut self, store_path: &Path, commit: String) {
+        self.trusted_commits.retain(|(path, _)| path != store_path);
+        self.trusted_commits.push((store_path.to_owned(), commit));
+    }
+
+    fn path() -> Option<PathBuf> {
+        ProjectDirs::from("io.github", "fin_ger", "PasswordStore")
+            .map(|dirs| dirs.config_dir().join("config"))
+    }
+
+    pub fn load() -> Self {
+        let mut config = Self::default();
+        let content = match Self::path().and_then(|path| fs::read_to_string(path).ok()) {
+            Some(content) => content,
+            None => return config,
+        };
+
+        for line in content.lines() {
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+
+            match key {
+                "crypto_backend" => {
+                    config.crypto_backend = CryptoBackendKind::from_name(value).unwrap_or_default();
+                },
+                "store_path" if !value.is_empty() => {
+                    config.store_path = Some(PathBuf::from(value));
+                },
+                "trusted_commit" => {
+                    // the hash comes first, as the path may contain spaces
+                    let mut parts = value.splitn(2, ' ');
+                    if let (Some(commit), Some(store_path)) = (parts.next(), parts.next()) {
+                        config.trusted_commits.push((PathBuf::from(store_path.trim()), commit.to_owned()));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        config
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let path = Self::path().ok_or_else(|| Error::Io {
+            path: PathBuf::new(),
+            kind: io::ErrorKind::Other,
+            message: "No configuration directory found for this user!".into(),
+        })?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| Error::io(parent, err))?;
+        }
+
+        let mut content = format!("crypto_backend = {}\n", self.crypto_backend.name());
+        if let Some(ref store_path) = self.store_path {
+            content.push_str(&format!("store_path = {}\n", store_path.display()));
+        }
+        for (store_path, commit) in &self.trusted_commits {
+            content.push_str(&format!("trusted_commit = {} {}\n", commit, store_path.display()));
+        }
+
+        fs::write(&path, content).map_err(|err| Error::io(&path, err))
+    }
+}
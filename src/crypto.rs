@@ -0,0 +1,617 @@
+use directories::ProjectDirs;
+use pgp::composed::{Deserializable, Message, SignedPublicKey, SignedSecretKey, StandaloneSignature};
+use pgp::crypto::SymmetricKeyAlgorithm;
+use pgp::ser::Serialize;
+use pgp::types::{CompressionAlgorithm, KeyTrait};
+
+use std::{io, fmt, fs, env, thread};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+
+const GPG_OPTS: &[&str] = &["--quiet", "--yes", "--compress-algo=none", "--no-encrypt-to", "--batch", "--use-agent"];
+
+#[derive(Clone, Debug)]
+pub enum CryptoError {
+    Io(String),
+    Backend(String),
+    NoRecipients,
+    KeyNotFound(String),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CryptoError::Io(msg) => write!(f, "{}", msg),
+            CryptoError::Backend(msg) => write!(f, "{}", msg),
+            CryptoError::NoRecipients => write!(f, "No recipients to encrypt to"),
+            CryptoError::KeyNotFound(key) => write!(f, "No public key found for '{}'", key),
+        }
+    }
+}
+
+impl From<io::Error> for CryptoError {
+    fn from(err: io::Error) -> Self {
+        CryptoError::Io(err.to_string())
+    }
+}
+
+impl From<pgp::errors::Error> for CryptoError {
+    fn from(err: pgp::errors::Error) -> Self {
+        CryptoError::Backend(err.to_string())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub fingerprint: String,
+    pub user_ids: Vec<String>,
+    pub secret: bool,
+}
+
+impl Key {
+    /// Whether `id` refers to this key, either by (a suffix of) its fingerprint or by one of its
+    /// user ids, the way `gpg -r` accepts them.
+    pub fn matches(&self, id: &str) -> bool {
+        let id = id.trim_start_matches("0x").to_uppercase();
+        let fingerprint = self.fingerprint.to_uppercase();
+
+        (id.len() >= 8 && fingerprint.ends_with(&id))
+            || self.user_ids.iter().any(|uid| uid.to_uppercase().contains(&id))
+    }
+}
+
+/// The key that made a valid signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signer {
+    /// The fingerprint of the key or subkey that made the signature.
+    pub fingerprint: String,
+    /// The fingerprint of the primary key, the same as `fingerprint` if it made the signature.
+    pub primary_fingerprint: String,
+}
+
+impl Signer {
+    /// Whether `fingerprint` is the signing key or its primary key, like `pass` accepts both in
+    /// `PASSWORD_STORE_SIGNING_KEY`.
+    pub fn matches(&self, fingerprint: &str) -> bool {
+        let fingerprint = fingerprint.trim_start_matches("0x");
+        self.fingerprint.eq_ignore_ascii_case(fingerprint)
+            || self.primary_fingerprint.eq_ignore_ascii_case(fingerprint)
+    }
+
+    /// The signer of a `[GNUPG:] VALIDSIG` status line. The primary key fingerprint is the last
+    /// field and missing for old GnuPG versions.
+    fn from_validsig(line: &str) -> Option<Self> {
+        let fields = line.strip_prefix("[GNUPG:] VALIDSIG ")?.split_whitespace().collect::<Vec<_>>();
+        let fingerprint = (*fields.first()?).to_owned();
+        let primary_fingerprint = match fields.get(9) {
+            Some(primary) => (*primary).to_owned(),
+            None => fingerprint.clone(),
+        };
+
+        Some(Self {
+            fingerprint,
+            primary_fingerprint,
+        })
+    }
+}
+
+/// The encryption layer of the password store.
+///
+/// The password list model only ever talks to this trait, so the store can be used with the `gpg`
+/// binary, a native OpenPGP implementation or, in tests, an in-memory mock without any keyring.
+pub trait CryptoBackend: fmt::Debug {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError>;
+    fn encrypt(&self, plaintext: &[u8], recipients: &[String]) -> Result<Vec<u8>, CryptoError>;
+    fn list_keys(&self, secret: bool) -> Result<Vec<Key>, CryptoError>;
+    /// Create a detached signature of `data` with one of the secret `keys`.
+    fn sign(&self, data: &[u8], keys: &[String]) -> Result<Vec<u8>, CryptoError>;
+    /// Verify the detached signature at `signature_path` of `data` and return the key that made
+    /// it.
+    fn verify(&self, data: &[u8], signature_path: &Path) -> Result<Signer, CryptoError>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CryptoBackendKind {
+    #[default]
+    GpgBinary,
+    Native,
+}
+
+impl CryptoBackendKind {
+    pub fn create(self) -> Rc<dyn CryptoBackend> {
+        match self {
+            CryptoBackendKind::GpgBinary => Rc::new(GpgBinaryBackend::default()),
+            CryptoBackendKind::Native => Rc::new(NativeBackend::default()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CryptoBackendKind::GpgBinary => "gpg",
+            CryptoBackendKind::Native => "native",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gpg" => Some(CryptoBackendKind::GpgBinary),
+            "native" => Some(CryptoBackendKind::Native),
+            _ => None,
+        }
+    }
+}
+
+/// Encrypts and decrypts by running the `gpg` binary, exactly like `pass` does.
+#[derive(Clone, Debug)]
+pub struct GpgBinaryBackend {
+    program: String,
+    homedir: Option<PathBuf>,
+}
+
+impl Default for GpgBinaryBackend {
+    fn default() -> Self {
+        Self {
+            program: "gpg".into(),
+            homedir: env::var_os("GNUPGHOME").map(PathBuf::from),
+        }
+    }
+}
+
+impl GpgBinaryBackend {
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        if let Some(ref homedir) = self.homedir {
+            command.arg("--homedir").arg(homedir);
+        }
+        command.args(GPG_OPTS);
+        command
+    }
+
+    fn run(&self, mut command: Command, input: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_vec();
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        writer.join().unwrap()?;
+
+        if !output.status.success() {
+            return Err(CryptoError::Backend(String::from_utf8_lossy(&output.stderr).trim().to_owned()));
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+impl CryptoBackend for GpgBinaryBackend {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let mut command = self.command();
+        command.arg("--decrypt");
+        self.run(command, ciphertext)
+    }
+
+    fn encrypt(&self, plaintext: &[u8], recipients: &[String]) -> Result<Vec<u8>, CryptoError> {
+        if recipients.is_empty() {
+            return Err(CryptoError::NoRecipients);
+        }
+
+        let mut command = self.command();
+        command.arg("--encrypt");
+        for recipient in recipients {
+            command.arg("--recipient").arg(recipient);
+        }
+        command.args(&["--output", "-"]);
+        self.run(command, plaintext)
+    }
+
+    fn list_keys(&self, secret: bool) -> Result<Vec<Key>, CryptoError> {
+        let mut command = self.command();
+        command.args(&["--with-colons", if secret { "--list-secret-keys" } else { "--list-keys" }]);
+        let output = String::from_utf8_lossy(&self.run(command, &[])?).into_owned();
+
+        let mut keys: Vec<Key> = vec![];
+        let mut expect_fingerprint = false;
+        for line in output.lines() {
+            let fields = line.split(':').collect::<Vec<_>>();
+            match fields[0] {
+                "pub" | "sec" => {
+                    keys.push(Key {
+                        fingerprint: String::new(),
+                        user_ids: vec![],
+                        secret,
+                    });
+                    expect_fingerprint = true;
+                },
+                "fpr" if expect_fingerprint => {
+                    if let (Some(key), Some(fingerprint)) = (keys.last_mut(), fields.get(9)) {
+                        key.fingerprint = (*fingerprint).to_owned();
+                    }
+                    expect_fingerprint = false;
+                },
+                "uid" => {
+                    if let (Some(key), Some(uid)) = (keys.last_mut(), fields.get(9)) {
+                        key.user_ids.push((*uid).to_owned());
+                    }
+                },
+                "sub" | "ssb" => {
+                    expect_fingerprint = false;
+                },
+                _ => {},
+            }
+        }
+
+        Ok(keys)
+    }
+
+    fn sign(&self, data: &[u8], keys: &[String]) -> Result<Vec<u8>, CryptoError> {
+        let mut command = self.command();
+        for key in keys {
+            command.arg("--default-key").arg(key);
+        }
+        command.args(&["--detach-sign", "--output", "-"]);
+        self.run(command, data)
+    }
+
+    fn verify(&self, data: &[u8], signature_path: &Path) -> Result<Signer, CryptoError> {
+        let mut command = self.command();
+        command.args(&["--status-fd=1", "--verify"]).arg(signature_path).arg("-");
+        let output = self.run(command, data)?;
+
+        String::from_utf8_lossy(&output)
+            .lines()
+            .find_map(Signer::from_validsig)
+            .ok_or_else(|| CryptoError::Backend("The signature is not valid".into()))
+    }
+}
+
+/// Encrypts and decrypts in-process with a native OpenPGP implementation.
+///
+/// As there is no access to the GnuPG keyring, keys are read from ASCII-armored files in the
+/// `keys` folder of the application data directory.
+#[derive(Clone, Debug)]
+pub struct NativeBackend {
+    key_dir: PathBuf,
+    passphrase: Option<String>,
+}
+
+impl Default for NativeBackend {
+    fn default() -> Self {
+        let key_dir = ProjectDirs::from("io.github", "fin_ger", "PasswordStore")
+            .map(|dirs| dirs.data_dir().join("keys"))
+            .unwrap_or_else(|| PathBuf::from("keys"));
+
+        Self {
+            key_dir,
+            passphrase: env::var("PASS_GTK_KEY_PASSPHRASE").ok(),
+        }
+    }
+}
+
+impl NativeBackend {
+    fn load_keys(&self) -> Result<(Vec<SignedSecretKey>, Vec<SignedPublicKey>), CryptoError> {
+        let mut secret_keys = vec![];
+        let mut public_keys = vec![];
+
+        let read_dir = match fs::read_dir(&self.key_dir) {
+            Ok(read_dir) => read_dir,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok((secret_keys, public_keys)),
+            Err(err) => return Err(err.into()),
+        };
+
+        for entry in read_dir.filter_map(|entry| entry.ok()) {
+            let armored = fs::read_to_string(entry.path())?;
+            if let Ok((key, _headers)) = SignedSecretKey::from_string(&armored) {
+                public_keys.push(key.public_key().sign(&key, || self.passphrase.clone().unwrap_or_default())?);
+                secret_keys.push(key);
+            } else if let Ok((key, _headers)) = SignedPublicKey::from_string(&armored) {
+                public_keys.push(key);
+            }
+        }
+
+        Ok((secret_keys, public_keys))
+    }
+
+    fn to_key(key: &SignedPublicKey, secret: bool) -> Key {
+        Key {
+            fingerprint: hex::encode_upper(key.fingerprint()),
+            user_ids: key.details.users.iter().map(|user| user.id.id().to_owned()).collect(),
+            secret,
+        }
+    }
+}
+
+impl CryptoBackend for NativeBackend {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let (secret_keys, _) = self.load_keys()?;
+        let message = if ciphertext.starts_with(b"-----BEGIN PGP MESSAGE-----") {
+            Message::from_string(&String::from_utf8_lossy(ciphertext))?.0
+        } else {
+            Message::from_bytes(ciphertext)?
+        };
+
+        let (mut decrypter, _key_ids) = message.decrypt(
+            String::new,
+            || self.passphrase.clone().unwrap_or_default(),
+            &secret_keys.iter().collect::<Vec<_>>(),
+        )?;
+        let decrypted = decrypter
+            .next()
+            .ok_or_else(|| CryptoError::Backend("Message does not contain any data".into()))??;
+
+        decrypted
+            .decompress()?
+            .get_content()?
+            .ok_or_else(|| CryptoError::Backend("Message does not contain any data".into()))
+    }
+
+    fn encrypt(&self, plaintext: &[u8], recipients: &[String]) -> Result<Vec<u8>, CryptoError> {
+        if recipients.is_empty() {
+            return Err(CryptoError::NoRecipients);
+        }
+
+        let (_, public_keys) = self.load_keys()?;
+        let mut encryption_keys = vec![];
+        for recipient in recipients {
+            let key = public_keys
+                .iter()
+                .find(|key| Self::to_key(key, false).matches(recipient))
+                .ok_or_else(|| CryptoError::KeyNotFound(recipient.to_owned()))?;
+            let subkey = key.public_subkeys
+                .iter()
+                .find(|subkey| subkey.is_encryption_key())
+                .ok_or_else(|| CryptoError::KeyNotFound(recipient.to_owned()))?;
+            encryption_keys.push(subkey);
+        }
+
+        let message = Message::new_literal_bytes("", plaintext)
+            .compress(CompressionAlgorithm::ZLIB)?
+            .encrypt_to_keys(&mut rand::thread_rng(), SymmetricKeyAlgorithm::AES256, &encryption_keys)?;
+
+        Ok(message.to_bytes()?)
+    }
+
+    fn list_keys(&self, secret: bool) -> Result<Vec<Key>, CryptoError> {
+        let (secret_keys, public_keys) = self.load_keys()?;
+        let secret_fingerprints = secret_keys.iter().map(|key| key.fingerprint()).collect::<Vec<_>>();
+
+        Ok(public_keys
+            .iter()
+            .filter(|key| !secret || secret_fingerprints.contains(&key.fingerprint()))
+            .map(|key| Self::to_key(key, secret_fingerprints.contains(&key.fingerprint())))
+            .collect())
+    }
+
+    fn sign(&self, _data: &[u8], _keys: &[String]) -> Result<Vec<u8>, CryptoError> {
+        Err(CryptoError::Backend("Signing is not supported by the native backend, please use GnuPG".into()))
+    }
+
+    fn verify(&self, data: &[u8], signature_path: &Path) -> Result<Signer, CryptoError> {
+        let (_, public_keys) = self.load_keys()?;
+        let bytes = fs::read(signature_path)?;
+        let signature = if bytes.starts_with(b"-----BEGIN PGP SIGNATURE-----") {
+            StandaloneSignature::from_string(&String::from_utf8_lossy(&bytes))?.0
+        } else {
+            StandaloneSignature::from_bytes(bytes.as_slice())?
+        };
+
+        public_keys
+            .iter()
+            .find_map(|key| {
+                let signing_key = if signature.verify(key, data).is_ok() {
+                    key.fingerprint()
+                } else {
+                    key.public_subkeys
+                        .iter()
+                        .find(|subkey| signature.verify(*subkey, data).is_ok())?
+                        .fingerprint()
+                };
+
+                Some(Signer {
+                    fingerprint: hex::encode_upper(signing_key),
+                    primary_fingerprint: hex::encode_upper(key.fingerprint()),
+                })
+            })
+            .ok_or_else(|| CryptoError::Backend("The signature is not valid".into()))
+    }
+}
+
+#[cfg(test)]
+const MOCK_HEADER: &str = "-----BEGIN MOCK MESSAGE-----";
+#[cfg(test)]
+const MOCK_SIGNATURE_HEADER: &str = "-----BEGIN MOCK SIGNATURE-----";
+
+/// A backend that does not encrypt at all, but keeps track of recipients, so the model can be
+/// tested without a keyring. It writes plaintext and is therefore only available in tests.
+#[cfg(test)]
+#[derive(Clone, Debug)]
+pub struct MockBackend {
+    keys: Vec<Key>,
+}
+
+#[cfg(test)]
+impl Default for MockBackend {
+    fn default() -> Self {
+        Self::new(vec![Key {
+            fingerprint: "0000000000000000000000000000000000000000".into(),
+            user_ids: vec!["Mock User <mock@example.com>".into()],
+            secret: true,
+        }])
+    }
+}
+
+#[cfg(test)]
+impl MockBackend {
+    pub fn new(keys: Vec<Key>) -> Self {
+        Self { keys }
+    }
+}
+
+#[cfg(test)]
+impl CryptoBackend for MockBackend {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let ciphertext = String::from_utf8_lossy(ciphertext);
+        let mut parts = ciphertext.splitn(3, '\n');
+        if parts.next() != Some(MOCK_HEADER) {
+            return Err(CryptoError::Backend("Not a mock message".into()));
+        }
+
+        let recipients = parts.next().unwrap_or("");
+        let can_decrypt = recipients
+            .split(',')
+            .any(|recipient| self.keys.iter().any(|key| key.secret && key.matches(recipient)));
+        if !can_decrypt {
+            return Err(CryptoError::Backend("No secret key available to decrypt".into()));
+        }
+
+        Ok(parts.next().unwrap_or("").as_bytes().to_vec())
+    }
+
+    fn encrypt(&self, plaintext: &[u8], recipients: &[String]) -> Result<Vec<u8>, CryptoError> {
+        if recipients.is_empty() {
+            return Err(CryptoError::NoRecipients);
+        }
+
+        for recipient in recipients {
+            if !self.keys.iter().any(|key| key.matches(recipient)) {
+                return Err(CryptoError::KeyNotFound(recipient.to_owned()));
+            }
+        }
+
+        let mut ciphertext = format!("{}\n{}\n", MOCK_HEADER, recipients.join(",")).into_bytes();
+        ciphertext.extend_from_slice(plaintext);
+        Ok(ciphertext)
+    }
+
+    fn list_keys(&self, secret: bool) -> Result<Vec<Key>, CryptoError> {
+        Ok(self.keys.iter().filter(|key| !secret || key.secret).cloned().collect())
+    }
+
+    fn sign(&self, data: &[u8], keys: &[String]) -> Result<Vec<u8>, CryptoError> {
+        let key = keys
+            .iter()
+            .find_map(|id| self.keys.iter().find(|key| key.secret && key.matches(id)))
+            .ok_or_else(|| CryptoError::KeyNotFound(keys.join(", ")))?;
+
+        Ok(format!("{}\n{}\n{}", MOCK_SIGNATURE_HEADER, key.fingerprint, hex::encode(data)).into_bytes())
+    }
+
+    fn verify(&self, data: &[u8], signature_path: &Path) -> Result<Signer, CryptoError> {
+        let signature = fs::read(signature_path)?;
+        let signature = String::from_utf8_lossy(&signature);
+        let mut parts = signature.splitn(3, '\n');
+        let valid = parts.next() == Some(MOCK_SIGNATURE_HEADER);
+        let fingerprint = parts.next().unwrap_or("");
+        let valid = valid
+            && parts.next() == Some(hex::encode(data).as_str())
+            && self.keys.iter().any(|key| key.fingerprint == fingerprint);
+
+        if valid {
+            Ok(Signer {
+                fingerprint: fingerprint.to_owned(),
+                primary_fingerprint: fingerprint.to_owned(),
+            })
+        } else {
+            Err(CryptoError::Backend("The signature is not valid".into()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipient() -> String {
+        "0000000000000000000000000000000000000000".into()
+    }
+
+    #[test]
+    fn mock_round_trip() {
+        let crypto = MockBackend::default();
+        let ciphertext = crypto.encrypt(b"secret\nlogin: me\n", &[recipient()]).unwrap();
+
+        assert_eq!(crypto.decrypt(&ciphertext).unwrap(), b"secret\nlogin: me\n");
+    }
+
+    #[test]
+    fn mock_requires_known_recipients() {
+        let crypto = MockBackend::default();
+
+        assert!(matches!(crypto.encrypt(b"secret", &[]), Err(CryptoError::NoRecipients)));
+        assert!(matches!(
+            crypto.encrypt(b"secret", &["someone@example.com".into()]),
+            Err(CryptoError::KeyNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn mock_requires_secret_key_to_decrypt() {
+        let public_only = MockBackend::new(vec![Key {
+            fingerprint: recipient(),
+            user_ids: vec![],
+            secret: false,
+        }]);
+        let ciphertext = public_only.encrypt(b"secret", &[recipient()]).unwrap();
+
+        assert!(public_only.decrypt(&ciphertext).is_err());
+        assert!(MockBackend::default().decrypt(&ciphertext).is_ok());
+    }
+
+    #[test]
+    fn key_matches_fingerprint_suffix_and_user_id() {
+        let key = Key {
+            fingerprint: "0123456789ABCDEF0123456789ABCDEF01234567".into(),
+            user_ids: vec!["Jane Doe <jane@example.com>".into()],
+            secret: false,
+        };
+
+        assert!(key.matches("0x89abcdef01234567"));
+        assert!(key.matches("jane@example.com"));
+        assert!(!key.matches("01234567890"));
+        assert!(!key.matches("john@example.com"));
+    }
+
+    #[test]
+    fn signer_of_gpg_status_accepts_subkey_and_primary_key() {
+        let signer = Signer::from_validsig(
+            "[GNUPG:] VALIDSIG 89ABCDEF89ABCDEF89ABCDEF89ABCDEF89ABCDEF 2021-01-01 1609459200 0 4 0 1 10 00 \
+             0123456789ABCDEF0123456789ABCDEF01234567",
+        )
+        .unwrap();
+
+        assert_eq!(signer.fingerprint, "89ABCDEF89ABCDEF89ABCDEF89ABCDEF89ABCDEF");
+        assert_eq!(signer.primary_fingerprint, "0123456789ABCDEF0123456789ABCDEF01234567");
+        assert!(signer.matches("89abcdef89abcdef89abcdef89abcdef89abcdef"));
+        assert!(signer.matches("0x0123456789ABCDEF0123456789ABCDEF01234567"));
+        assert!(!signer.matches("0000000000000000000000000000000000000000"));
+        assert_eq!(Signer::from_validsig("[GNUPG:] GOODSIG 0123456789ABCDEF Jane"), None);
+    }
+
+    #[test]
+    fn mock_signatures_are_verified() {
+        let crypto = MockBackend::default();
+        let signature = crypto.sign(b"data", &[recipient()]).unwrap();
+        let path = env::temp_dir().join(format!("pass-gtk-crypto-test-{}.sig", std::process::id()));
+        fs::write(&path, signature).unwrap();
+
+        let signer = crypto.verify(b"data", &path);
+        let tampered = crypto.verify(b"other data", &path);
+        let _ = fs::remove_file(&path);
+
+        assert!(signer.unwrap().matches(&recipient()));
+        assert!(tampered.is_err());
+    }
+
+    #[test]
+    fn mock_is_not_a_selectable_backend() {
+        assert_eq!(CryptoBackendKind::from_name("mock"), None);
+        assert_eq!(CryptoBackendKind::from_name("gpg"), Some(CryptoBackendKind::GpgBinary));
+        assert_eq!(CryptoBackendKind::from_name("native"), Some(CryptoBackendKind::Native));
+    }
+}
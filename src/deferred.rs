@@ -0,0 +1,17 @@
+use vgtk::lib::{glib, gio};
+use vgtk::lib::gio::prelude::*;
+
+/// Activate the application action `name` (without the `app.` prefix) after `delay_ms`
+/// milliseconds.
+///
+/// Component updates can only render once they returned, so long running jobs are split into
+/// steps and every step schedules the next one through its action. This way the view is rendered
+/// in between and stays responsive.
+pub fn activate_action_later(name: &'static str, delay_ms: u32) {
+    glib::timeout_add_local(delay_ms, move || {
+        if let Some(app) = gio::Application::get_default() {
+            app.activate_action(name, None);
+        }
+        glib::Continue(false)
+    });
+}
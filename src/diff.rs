@@ -0,0 +1,120 @@
+/// A line of a line-based diff between two texts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+}
+
+/// The pairs of line indices of `old` and `new` that are part of their longest common
+/// subsequence, in ascending order.
+fn matching_lines(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut matches = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    matches
+}
+
+/// Compute a line diff between `old` and `new` based on their longest common subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    let mut diff = vec![];
+    let (mut i, mut j) = (0, 0);
+    for (old_match, new_match) in matching_lines(&old, &new).into_iter().chain(Some((old.len(), new.len()))) {
+        diff.extend(old[i..old_match].iter().map(|line| DiffLine::Removed((*line).to_owned())));
+        diff.extend(new[j..new_match].iter().map(|line| DiffLine::Added((*line).to_owned())));
+        if old_match < old.len() {
+            diff.push(DiffLine::Unchanged(old[old_match].to_owned()));
+        }
+        i = old_match + 1;
+        j = new_match + 1;
+    }
+
+    diff
+}
+
+/// Merge the changes from `base` to `ours` and from `base` to `theirs` like `diff3 -m` does.
+/// Regions changed differently on both sides are wrapped in conflict markers using the given
+/// labels. Returns the merged text and whether it contains conflicts.
+pub fn merge3(base: &str, ours: &str, theirs: &str, ours_label: &str, theirs_label: &str) -> (String, bool) {
+    let base_lines = base.lines().collect::<Vec<_>>();
+    let our_lines = ours.lines().collect::<Vec<_>>();
+    let their_lines = theirs.lines().collect::<Vec<_>>();
+
+    // base lines that are unchanged on both sides split the texts into regions
+    let their_matches = matching_lines(&base_lines, &their_lines);
+    let sync_lines = matching_lines(&base_lines, &our_lines)
+        .into_iter()
+        .filter_map(|(base_index, our_index)| {
+            their_matches
+                .iter()
+                .find(|(their_base_index, _)| *their_base_index == base_index)
+                .map(|(_, their_index)| (base_index, our_index, *their_index))
+        })
+        .chain(Some((base_lines.len(), our_lines.len(), their_lines.len())));
+
+    let mut merged: Vec<String> = vec![];
+    let mut has_conflicts = false;
+    let (mut base_start, mut our_start, mut their_start) = (0, 0, 0);
+    let to_owned = |lines: &[&str]| lines.iter().map(|line| (*line).to_owned()).collect::<Vec<_>>();
+
+    for (base_end, our_end, their_end) in sync_lines {
+        let base_region = &base_lines[base_start..base_end];
+        let our_region = &our_lines[our_start..our_end];
+        let their_region = &their_lines[their_start..their_end];
+
+        if our_region == their_region || their_region == base_region {
+            merged.extend(to_owned(our_region));
+        } else if our_region == base_region {
+            merged.extend(to_owned(their_region));
+        } else {
+            has_conflicts = true;
+            merged.push(format!("<<<<<<< {}", ours_label));
+            merged.extend(to_owned(our_region));
+            merged.push("||||||| base".to_owned());
+            merged.extend(to_owned(base_region));
+            merged.push("=======".to_owned());
+            merged.extend(to_owned(their_region));
+            merged.push(format!(">>>>>>> {}", theirs_label));
+        }
+
+        if base_end < base_lines.len() {
+            merged.push(base_lines[base_end].to_owned());
+        }
+        base_start = base_end + 1;
+        our_start = our_end + 1;
+        their_start = their_end + 1;
+    }
+
+    let mut result = merged.join("\n");
+    if ours.ends_with('\n') || theirs.ends_with('\n') {
+        result.push('\n');
+    }
+
+    (result, has_conflicts)
+}
@@ -0,0 +1,97 @@
+use crate::crypto::CryptoError;
+use crate::git::GitError;
+
+use std::{io, fmt};
+use std::path::{Path, PathBuf};
+
+/// An error of the password store that is shown to the user.
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io {
+        path: PathBuf,
+        kind: io::ErrorKind,
+        message: String,
+    },
+    Crypto(CryptoError),
+    Git(GitError),
+    /// A file has invalid content, e.g. a `.gpg-id` that is not valid UTF-8.
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+/// What the user can do about an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorAction {
+    Retry,
+    ChooseStore,
+    OpenPreferences,
+}
+
+impl Error {
+    /// An error accessing `path`. Invalid data is reported as a parse error.
+    pub fn io(path: &Path, err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::InvalidData => Error::Parse {
+                path: path.to_owned(),
+                message: err.to_string(),
+            },
+            kind => Error::Io {
+                path: path.to_owned(),
+                kind,
+                message: err.to_string(),
+            },
+        }
+    }
+
+    pub fn actions(&self) -> Vec<ErrorAction> {
+        match self {
+            Error::Io { kind: io::ErrorKind::NotFound, .. }
+            | Error::Io { kind: io::ErrorKind::PermissionDenied, .. } => {
+                vec![ErrorAction::Retry, ErrorAction::ChooseStore]
+            },
+            Error::Io { .. } | Error::Parse { .. } | Error::Git(_) => vec![ErrorAction::Retry],
+            Error::Crypto(_) => vec![ErrorAction::Retry, ErrorAction::OpenPreferences],
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, kind: io::ErrorKind::PermissionDenied, .. } => {
+                write!(f, "Permission denied: {}", path.display())
+            },
+            Error::Io { path, message, .. } => write!(f, "Could not access {}: {}", path.display(), message),
+            Error::Crypto(err) => write!(f, "{}", err),
+            Error::Git(err) => write!(f, "{}", err),
+            Error::Parse { path, message } => write!(f, "Could not read {}: {}", path.display(), message),
+        }
+    }
+}
+
+impl From<CryptoError> for Error {
+    fn from(err: CryptoError) -> Self {
+        Error::Crypto(err)
+    }
+}
+
+impl From<GitError> for Error {
+    fn from(err: GitError) -> Self {
+        Error::Git(err)
+    }
+}
+
+/// For helpers that only report the message of an error, e.g. in a dialog.
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::Io { kind, .. } => kind,
+            Error::Parse { .. } => io::ErrorKind::InvalidData,
+            Error::Crypto(_) | Error::Git(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err.to_string())
+    }
+}
@@ -1,6 +1,11 @@
+use crate::qr;
+
+use qrcode::Color;
 use vgtk::lib::gtk::*;
 use vgtk::lib::glib::{ObjectType, ObjectExt, GString};
 
+use std::f64::consts::PI;
+
 pub trait UserDataPathExt: WidgetExt + ObjectType {
     fn set_user_data_path(&self, path: String) {
         unsafe { self.set_data("path", path); }
@@ -52,3 +57,92 @@ pub trait ScrollPolicyExt: ScrolledWindowExt {
 }
 
 impl<A> ScrollPolicyExt for A where A: ScrolledWindowExt {}
+
+/// Turns a `DrawingArea` into a ring that is filled according to `countdown_fraction`, e.g. for
+/// the time left until a one-time password expires.
+pub trait CountdownRingExt: WidgetExt + ObjectType {
+    fn get_countdown_fraction(&self) -> f64 {
+        unsafe { self.get_data::<f64>("countdown-fraction") }.cloned().unwrap_or(0.0)
+    }
+
+    fn set_countdown_fraction(&self, fraction: f64) {
+        let connected = unsafe { self.get_data::<bool>("countdown-connected") }.is_some();
+        unsafe { self.set_data("countdown-fraction", fraction.max(0.0).min(1.0)); }
+
+        if !connected {
+            unsafe { self.set_data("countdown-connected", true); }
+            self.connect_draw(|widget, cr| {
+                let fraction = widget.get_countdown_fraction();
+                let width = widget.get_allocated_width() as f64;
+                let height = widget.get_allocated_height() as f64;
+                let line_width = 3.0;
+                let radius = (width.min(height) - line_width) / 2.0;
+                let color = widget.get_style_context().get_color(widget.get_state_flags());
+
+                cr.set_line_width(line_width);
+                cr.set_source_rgba(color.red, color.green, color.blue, color.alpha * 0.2);
+                cr.arc(width / 2.0, height / 2.0, radius, 0.0, 2.0 * PI);
+                cr.stroke();
+
+                cr.set_source_rgba(color.red, color.green, color.blue, color.alpha);
+                cr.arc(width / 2.0, height / 2.0, radius, -PI / 2.0, -PI / 2.0 + 2.0 * PI * fraction);
+                cr.stroke();
+
+                Inhibit(false)
+            });
+        }
+
+        self.queue_draw();
+    }
+}
+
+impl CountdownRingExt for DrawingArea {}
+
+/// Turns a `DrawingArea` into the QR code of `qr_code`, scaled to fit the widget.
+pub trait QrCodeExt: WidgetExt + ObjectType {
+    fn get_qr_code(&self) -> String {
+        unsafe { self.get_data::<String>("qr-code") }.cloned().unwrap_or_default()
+    }
+
+    fn set_qr_code(&self, data: String) {
+        let connected = unsafe { self.get_data::<bool>("qr-code-connected") }.is_some();
+        unsafe { self.set_data("qr-code", data); }
+
+        if !connected {
+            unsafe { self.set_data("qr-code-connected", true); }
+            self.connect_draw(|widget, cr| {
+                let code = match qr::encode(&widget.get_qr_code()) {
+                    Ok(code) => code,
+                    Err(_) => return Inhibit(false),
+                };
+                // QR codes need a light quiet zone of four modules around them
+                let modules = code.width() + 8;
+                let width = widget.get_allocated_width() as f64;
+                let height = widget.get_allocated_height() as f64;
+                let module_size = (width.min(height) / modules as f64).floor();
+                let left = ((width - module_size * modules as f64) / 2.0).floor();
+                let top = ((height - module_size * modules as f64) / 2.0).floor();
+
+                cr.set_source_rgb(1.0, 1.0, 1.0);
+                cr.rectangle(left, top, module_size * modules as f64, module_size * modules as f64);
+                cr.fill();
+
+                cr.set_source_rgb(0.0, 0.0, 0.0);
+                for (index, color) in code.to_colors().into_iter().enumerate() {
+                    if color == Color::Dark {
+                        let x = (index % code.width() + 4) as f64;
+                        let y = (index / code.width() + 4) as f64;
+                        cr.rectangle(left + x * module_size, top + y * module_size, module_size, module_size);
+                    }
+                }
+                cr.fill();
+
+                Inhibit(false)
+            });
+        }
+
+        self.queue_draw();
+    }
+}
+
+impl QrCodeExt for DrawingArea {}
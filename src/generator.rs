@@ -0,0 +1,216 @@
+use rand::Rng;
+use rand::rngs::OsRng;
+
+use std::{io, env};
+
+const DEFAULT_LENGTH: usize = 25;
+const DEFAULT_CHARACTER_SET: &str = "[:punct:][:alnum:]";
+const DEFAULT_CHARACTER_SET_NO_SYMBOLS: &str = "[:alnum:]";
+
+/// Options for generating passwords, mirroring the flags of `pass generate`.
+#[derive(Clone, Debug)]
+pub struct GeneratorOptions {
+    pub length: usize,
+    /// `--no-symbols`: use `PASSWORD_STORE_CHARACTER_SET_NO_SYMBOLS` instead of
+    /// `PASSWORD_STORE_CHARACTER_SET`.
+    pub no_symbols: bool,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        let length = env::var("PASSWORD_STORE_GENERATED_LENGTH")
+            .ok()
+            .and_then(|length| length.parse().ok())
+            .filter(|&length| length > 0)
+            .unwrap_or(DEFAULT_LENGTH);
+
+        Self {
+            length,
+            no_symbols: false,
+        }
+    }
+}
+
+/// The characters generated passwords are made of, read from the same environment variables
+/// `pass` uses. The sets are given in `tr` syntax.
+pub fn character_set(no_symbols: bool) -> Vec<char> {
+    let set = if no_symbols {
+        env::var("PASSWORD_STORE_CHARACTER_SET_NO_SYMBOLS")
+            .unwrap_or_else(|_| DEFAULT_CHARACTER_SET_NO_SYMBOLS.into())
+    } else {
+        env::var("PASSWORD_STORE_CHARACTER_SET")
+            .unwrap_or_else(|_| DEFAULT_CHARACTER_SET.into())
+    };
+
+    let mut characters = expand_tr_set(&set);
+    characters.sort();
+    characters.dedup();
+    characters
+}
+
+pub fn generate(options: &GeneratorOptions) -> io::Result<String> {
+    let characters = character_set(options.no_symbols);
+    if characters.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The password character set is empty"));
+    }
+    if options.length == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The password length must be greater than zero"));
+    }
+
+    let mut rng = OsRng;
+    Ok((0..options.length)
+        .map(|_| characters[rng.gen_range(0, characters.len())])
+        .collect())
+}
+
+fn character_class(name: &str) -> Option<Vec<char>> {
+    let ascii = (0u8..128).map(char::from);
+    let class = match name {
+        "alnum" => ascii.filter(|c| c.is_ascii_alphanumeric()).collect(),
+        "alpha" => ascii.filter(|c| c.is_ascii_alphabetic()).collect(),
+        "digit" => ascii.filter(|c| c.is_ascii_digit()).collect(),
+        "lower" => ascii.filter(|c| c.is_ascii_lowercase()).collect(),
+        "upper" => ascii.filter(|c| c.is_ascii_uppercase()).collect(),
+        "punct" => ascii.filter(|c| c.is_ascii_punctuation()).collect(),
+        "xdigit" => ascii.filter(|c| c.is_ascii_hexdigit()).collect(),
+        "graph" => ascii.filter(|c| c.is_ascii_graphic()).collect(),
+        "print" => ascii.filter(|c| c.is_ascii_graphic() || *c == ' ').collect(),
+        "space" => ascii.filter(|c| c.is_ascii_whitespace()).collect(),
+        "blank" => vec![' ', '\t'],
+        _ => return None,
+    };
+
+    Some(class)
+}
+
+/// Expand a `tr` character set like `[:alnum:]_a-f` into the characters it describes.
+fn expand_tr_set(set: &str) -> Vec<char> {
+    let chars = set.chars().collect::<Vec<_>>();
+    let mut characters = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == '[' && chars.get(i + 1) == Some(&':') {
+            let rest = chars[i + 2..].iter().collect::<String>();
+            if let Some(end) = rest.find(":]") {
+                if let Some(class) = character_class(&rest[..end]) {
+                    characters.extend(class);
+                    i += 2 + rest[..end].chars().count() + 2;
+                    continue;
+                }
+            }
+        }
+
+        let (start, next) = if chars[i] == '\\' && i + 1 < chars.len() {
+            (unescape(chars[i + 1]), i + 2)
+        } else {
+            (chars[i], i + 1)
+        };
+
+        if chars.get(next) == Some(&'-') && next + 1 < chars.len() {
+            let end = chars[next + 1];
+            characters.extend((start..=end).filter(|c| !c.is_control()));
+            i = next + 2;
+        } else {
+            characters.push(start);
+            i = next;
+        }
+    }
+
+    characters
+}
+
+fn unescape(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn expands_character_classes() {
+        let alnum = expand_tr_set("[:alnum:]");
+        assert_eq!(alnum.len(), 62);
+        assert!(alnum.iter().all(|c| c.is_ascii_alphanumeric()));
+
+        let punct = expand_tr_set("[:punct:]");
+        assert_eq!(punct.len(), 32);
+        assert!(punct.contains(&'!') && punct.contains(&'~') && !punct.contains(&'a'));
+
+        assert_eq!(expand_tr_set("[:digit:]x"), chars("0123456789x"));
+    }
+
+    #[test]
+    fn unknown_classes_are_taken_literally() {
+        assert_eq!(expand_tr_set("[:nope:]"), chars("[:nope:]"));
+    }
+
+    #[test]
+    fn expands_ranges() {
+        assert_eq!(expand_tr_set("a-e"), chars("abcde"));
+        assert_eq!(expand_tr_set("0-3x-z"), chars("0123xyz"));
+    }
+
+    #[test]
+    fn trailing_dash_is_literal() {
+        assert_eq!(expand_tr_set("ab-"), chars("ab-"));
+        assert_eq!(expand_tr_set("-a"), chars("-a"));
+    }
+
+    #[test]
+    fn expands_escapes() {
+        assert_eq!(expand_tr_set("\\-"), chars("-"));
+        assert_eq!(expand_tr_set("a\\-z"), chars("a-z"));
+        assert_eq!(expand_tr_set("\\n\\t\\\\"), chars("\n\t\\"));
+    }
+
+    #[test]
+    fn generates_from_the_character_set() {
+        let options = GeneratorOptions {
+            length: 64,
+            no_symbols: true,
+        };
+        let password = generate(&options).unwrap();
+
+        assert_eq!(password.chars().count(), 64);
+        // alphanumeric with the default and the overridden set of `environment_overrides_defaults`
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(generate(&GeneratorOptions { length: 0, no_symbols: true }).is_err());
+    }
+
+    // the only test that changes the environment, as tests run in parallel
+    #[test]
+    fn environment_overrides_defaults() {
+        assert_eq!(character_set(true).len(), 62);
+
+        env::set_var("PASSWORD_STORE_CHARACTER_SET", "a-c[:digit:]a");
+        env::set_var("PASSWORD_STORE_CHARACTER_SET_NO_SYMBOLS", "xyz");
+        env::set_var("PASSWORD_STORE_GENERATED_LENGTH", "12");
+        let character_set_override = character_set(false);
+        let no_symbols_override = character_set(true);
+        let length_override = GeneratorOptions::default().length;
+
+        env::set_var("PASSWORD_STORE_GENERATED_LENGTH", "0");
+        let invalid_length = GeneratorOptions::default().length;
+
+        env::remove_var("PASSWORD_STORE_CHARACTER_SET");
+        env::remove_var("PASSWORD_STORE_CHARACTER_SET_NO_SYMBOLS");
+        env::remove_var("PASSWORD_STORE_GENERATED_LENGTH");
+
+        assert_eq!(character_set_override, chars("0123456789abc"));
+        assert_eq!(no_symbols_override, chars("xyz"));
+        assert_eq!(length_override, 12);
+        assert_eq!(invalid_length, DEFAULT_LENGTH);
+        assert_eq!(GeneratorOptions::default().length, DEFAULT_LENGTH);
+        assert_eq!(character_set(false).len(), 94);
+    }
+}
@@ -0,0 +1,638 @@
+use std::{io, fmt, fs, thread};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+/// The line `pass git init` adds to `.gitattributes` to diff entries through the textconv driver.
+pub const GPG_DIFF_ATTRIBUTE: &str = "*.gpg diff=gpg";
+
+/// The command `pass git init` sets as textconv driver to show decrypted diffs.
+const GPG_TEXTCONV: &str = "gpg -d --quiet --yes --compress-algo=none --no-encrypt-to";
+
+#[derive(Clone, Debug)]
+pub enum GitError {
+    Io(String),
+    Command {
+        command: String,
+        stderr: String,
+    },
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GitError::Io(msg) => write!(f, "Could not run git: {}", msg),
+            GitError::Command { command, stderr } => write!(f, "git {} failed: {}", command, stderr),
+        }
+    }
+}
+
+impl From<io::Error> for GitError {
+    fn from(err: io::Error) -> Self {
+        GitError::Io(err.to_string())
+    }
+}
+
+/// Whether the content of a `.gitattributes` file assigns the gpg diff driver to `.gpg` files.
+pub fn has_gpg_diff_attribute(attributes: &str) -> bool {
+    attributes.lines().any(|line| {
+        let mut words = line.split_whitespace();
+        words.next() == Some("*.gpg") && words.any(|attribute| attribute == "diff=gpg")
+    })
+}
+
+/// A commit touching an entry.
+#[derive(Clone, Debug)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+    /// The path of the entry in this commit, relative to the repository. It differs from the
+    /// current path if the entry was renamed afterwards.
+    pub path: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// The result of verifying the signature of a commit, as reported by git's `%G?` placeholder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Good,
+    /// A good signature by a key that is not trusted.
+    UntrustedKey,
+    Bad,
+    ExpiredSignature,
+    ExpiredKey,
+    RevokedKey,
+    /// The signature cannot be checked, usually because the key is unknown.
+    UnknownKey,
+    Unsigned,
+}
+
+impl SignatureStatus {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "G" => SignatureStatus::Good,
+            "U" => SignatureStatus::UntrustedKey,
+            "B" => SignatureStatus::Bad,
+            "X" => SignatureStatus::ExpiredSignature,
+            "Y" => SignatureStatus::ExpiredKey,
+            "R" => SignatureStatus::RevokedKey,
+            "E" => SignatureStatus::UnknownKey,
+            _ => SignatureStatus::Unsigned,
+        }
+    }
+
+    pub fn is_trusted(self) -> bool {
+        self == SignatureStatus::Good
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            SignatureStatus::Good => "good signature",
+            SignatureStatus::UntrustedKey => "signed by an untrusted key",
+            SignatureStatus::Bad => "bad signature",
+            SignatureStatus::ExpiredSignature => "expired signature",
+            SignatureStatus::ExpiredKey => "signed by an expired key",
+            SignatureStatus::RevokedKey => "signed by a revoked key",
+            SignatureStatus::UnknownKey => "signed by an unknown key",
+            SignatureStatus::Unsigned => "not signed",
+        }
+    }
+}
+
+/// A commit together with the verification result of its signature.
+#[derive(Clone, Debug)]
+pub struct SignedCommit {
+    pub hash: String,
+    pub author: String,
+    pub subject: String,
+    pub status: SignatureStatus,
+    /// The id of the signing key, if the commit is signed.
+    pub key: Option<String>,
+    /// The files changed by the commit, relative to the repository.
+    pub files: Vec<String>,
+}
+
+impl SignedCommit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// A git command running in the background, like a pull or push waiting for the network.
+#[derive(Debug)]
+pub struct GitProcess {
+    command: String,
+    child: Child,
+    /// Reads stderr while the command runs, so a chatty command cannot fill the pipe and block.
+    stderr: Option<JoinHandle<String>>,
+}
+
+impl GitProcess {
+    /// The result of the command once it exited. Does not block.
+    pub fn try_finish(&mut self) -> Option<Result<(), GitError>> {
+        let status = match self.child.try_wait() {
+            Ok(status) => status?,
+            Err(err) => return Some(Err(err.into())),
+        };
+        let stderr = self.stderr
+            .take()
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        if status.success() {
+            return Some(Ok(()));
+        }
+
+        Some(Err(GitError::Command {
+            command: self.command.clone(),
+            stderr: stderr.trim().to_owned(),
+        }))
+    }
+}
+
+/// The git repository a password store lives in, driven through the `git` binary.
+#[derive(Clone, Debug)]
+pub struct GitRepository {
+    path: PathBuf,
+}
+
+impl GitRepository {
+    /// Open the repository of the password store at `path`, if the store is managed with git.
+    pub fn open(path: &Path) -> Option<Self> {
+        if path.is_dir() && path.join(".git").exists() {
+            Some(Self { path: path.to_owned() })
+        } else {
+            None
+        }
+    }
+
+    /// Create a new repository for the password store at `path`.
+    pub fn init(path: &Path) -> Result<Self, GitError> {
+        let repository = Self { path: path.to_owned() };
+        repository.run(&["init"])?;
+        Ok(repository)
+    }
+
+    /// Clone the repository at `url` into the new directory `path`.
+    pub fn clone_from(url: &str, path: &Path) -> Result<Self, GitError> {
+        let parent = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(parent)?;
+        Self { path: parent.to_owned() }.run(&["clone", "--", url, &path.to_string_lossy()])?;
+        Ok(Self { path: path.to_owned() })
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String, GitError> {
+        self.run_bytes(args).map(|stdout| String::from_utf8_lossy(&stdout).into_owned())
+    }
+
+    fn run_bytes(&self, args: &[&str]) -> Result<Vec<u8>, GitError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(GitError::Command {
+                command: args.first().unwrap_or(&"").to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            });
+        }
+
+        Ok(output.stdout)
+    }
+
+    /// Start git in the background. It must not ask for credentials on a terminal, as nobody
+    /// would see the prompt.
+    fn spawn(&self, args: &[&str]) -> Result<GitProcess, GitError> {
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stderr = child.stderr.take().map(|mut pipe| {
+            thread::spawn(move || {
+                let mut stderr = vec![];
+                let _ = pipe.read_to_end(&mut stderr);
+                String::from_utf8_lossy(&stderr).into_owned()
+            })
+        });
+
+        Ok(GitProcess {
+            command: args.first().unwrap_or(&"").to_string(),
+            child,
+            stderr,
+        })
+    }
+
+    fn relative_path(&self, path: &Path) -> String {
+        match path.strip_prefix(&self.path).unwrap_or(path).to_string_lossy().as_ref() {
+            // git rejects empty pathspecs
+            "" => ".".to_owned(),
+            path => path.to_owned(),
+        }
+    }
+
+    /// Whether `pass.signcommits` asks for signed commits.
+    fn signs_commits(&self) -> bool {
+        self.run(&["config", "--bool", "--get", "pass.signcommits"])
+            .map(|value| value.trim() == "true")
+            .unwrap_or(false)
+    }
+
+    /// Stage all changes to `paths`, including removals. Changes that were undone in the working
+    /// tree are unstaged again.
+    pub fn stage(&self, paths: &[&Path]) -> Result<(), GitError> {
+        let paths = paths.iter().map(|path| self.relative_path(path)).collect::<Vec<_>>();
+
+        let mut add = vec!["add", "--all", "--"];
+        add.extend(paths.iter().map(|path| path.as_str()));
+        self.run(&add)?;
+        Ok(())
+    }
+
+    /// Stage all changes to `paths`, including removals, and commit them with `message`. Commits
+    /// are signed if `pass.signcommits` is enabled, exactly like `pass` does it.
+    pub fn commit(&self, paths: &[&Path], message: &str) -> Result<(), GitError> {
+        self.stage(paths)?;
+        let paths = paths.iter().map(|path| self.relative_path(path)).collect::<Vec<_>>();
+
+        let mut status = vec!["status", "--porcelain", "--"];
+        status.extend(paths.iter().map(|path| path.as_str()));
+        if self.run(&status)?.trim().is_empty() {
+            return Ok(());
+        }
+
+        if self.signs_commits() {
+            self.run(&["commit", "-S", "-m", message])?;
+        } else {
+            self.run(&["commit", "-m", message])?;
+        }
+
+        Ok(())
+    }
+
+    /// All commits that touched the file at `path`, newest first, following renames.
+    pub fn log(&self, path: &Path) -> Result<Vec<Commit>, GitError> {
+        let path = self.relative_path(path);
+        let output = self.run(&[
+            "log",
+            "--follow",
+            "--name-only",
+            "--date=format:%Y-%m-%d %H:%M",
+            "--format=%x1e%H%x1f%an%x1f%ad%x1f%s",
+            "--",
+            &path,
+        ])?;
+
+        Ok(output
+            .split('\x1e')
+            .filter(|record| !record.trim().is_empty())
+            .filter_map(|record| {
+                let mut lines = record.lines();
+                let header = lines.next()?.split('\x1f').collect::<Vec<_>>();
+                let path = lines.find(|line| !line.trim().is_empty()).unwrap_or(&path);
+
+                Some(Commit {
+                    hash: header.first()?.to_string(),
+                    author: header.get(1)?.to_string(),
+                    date: header.get(2)?.to_string(),
+                    subject: header.get(3)?.to_string(),
+                    path: path.to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    /// The content of the file at `path` (relative to the repository) in `revision`.
+    pub fn show_file(&self, revision: &str, path: &str) -> Result<Vec<u8>, GitError> {
+        self.run_bytes(&["show", &format!("{}:{}", revision, path)])
+    }
+
+    /// Start `git pull --rebase` in the background, to rebase local commits onto the upstream branch.
+    pub fn pull(&self) -> Result<GitProcess, GitError> {
+        self.spawn(&["pull", "--rebase"])
+    }
+
+    /// Start `git push` in the background.
+    pub fn push(&self) -> Result<GitProcess, GitError> {
+        self.spawn(&["push"])
+    }
+
+    /// How many commits the current branch is ahead and behind of its upstream branch, or `None`
+    /// if there is no upstream branch.
+    pub fn ahead_behind(&self) -> Option<(usize, usize)> {
+        let output = self.run(&["rev-list", "--left-right", "--count", "HEAD...@{upstream}"]).ok()?;
+        let mut counts = output.split_whitespace().map(|count| count.parse().ok());
+
+        Some((counts.next()??, counts.next()??))
+    }
+
+    /// When the repository was last fetched from its remote.
+    pub fn last_fetch(&self) -> Option<SystemTime> {
+        fs::metadata(self.path.join(".git").join("FETCH_HEAD"))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// The files with unresolved merge conflicts, relative to the repository.
+    pub fn conflicted_files(&self) -> Result<Vec<String>, GitError> {
+        Ok(self.run(&["diff", "--name-only", "--diff-filter=U"])?
+            .lines()
+            .map(|line| line.to_owned())
+            .collect())
+    }
+
+    /// Whether a `git pull --rebase` stopped in the middle of a rebase.
+    pub fn is_rebasing(&self) -> bool {
+        let git_dir = self.path.join(".git");
+        git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists()
+    }
+
+    /// The content of a conflicted file in the given merge stage: 1 is the common base, 2 is
+    /// "ours" and 3 is "theirs". Returns `None` if the file does not exist in that stage.
+    pub fn conflict_stage(&self, stage: u8, path: &str) -> Option<Vec<u8>> {
+        self.run_bytes(&["show", &format!(":{}:{}", stage, path)]).ok()
+    }
+
+    /// Mark `path` as resolved and continue the interrupted rebase or merge once no conflicts are
+    /// left.
+    pub fn resolve(&self, path: &str) -> Result<(), GitError> {
+        self.run(&["add", "--", path])?;
+        self.continue_resolved()
+    }
+
+    /// Resolve the conflict of `path` by deleting it, e.g. because one side removed the entry
+    /// and the other one changed it.
+    pub fn resolve_deleted(&self, path: &str) -> Result<(), GitError> {
+        self.run(&["rm", "--quiet", "--force", "--", path])?;
+        self.continue_resolved()
+    }
+
+    /// Continue the interrupted rebase or merge once no conflicts are left. The commits are
+    /// signed if `pass.signcommits` is enabled.
+    fn continue_resolved(&self) -> Result<(), GitError> {
+        if !self.conflicted_files()?.is_empty() {
+            return Ok(());
+        }
+
+        let mut args = vec!["-c", "core.editor=true"];
+        if self.signs_commits() {
+            args.extend(&["-c", "commit.gpgsign=true"]);
+        }
+        if self.is_rebasing() {
+            args.extend(&["rebase", "--continue"]);
+        } else {
+            args.extend(&["commit", "--no-edit"]);
+        }
+        self.run(&args)?;
+
+        Ok(())
+    }
+
+    fn config(&self, key: &str) -> Option<String> {
+        self.run(&["config", "--local", "--get", key])
+            .ok()
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Problems with the setup that makes `git log -p` and `git diff` show decrypted entries, like
+    /// `pass git init` creates it. Returns an empty list if the setup is complete.
+    pub fn gpg_diff_problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+
+        let has_attribute = fs::read_to_string(self.path.join(".gitattributes"))
+            .map(|attributes| has_gpg_diff_attribute(&attributes))
+            .unwrap_or(false);
+        if !has_attribute {
+            problems.push(format!(".gitattributes does not contain “{}”", GPG_DIFF_ATTRIBUTE));
+        }
+
+        if self.config("diff.gpg.binary").as_deref() != Some("true") {
+            problems.push("diff.gpg.binary is not enabled".to_owned());
+        }
+
+        match self.config("diff.gpg.textconv") {
+            None => problems.push("diff.gpg.textconv is not set".to_owned()),
+            Some(textconv) => {
+                let mut words = textconv.split_whitespace();
+                let program = words.next().unwrap_or("");
+                if !words.any(|arg| arg == "-d" || arg == "--decrypt") {
+                    problems.push(format!("diff.gpg.textconv “{}” does not decrypt", textconv));
+                } else if Command::new(program).arg("--version").output().is_err() {
+                    problems.push(format!("diff.gpg.textconv uses “{}” which cannot be run", program));
+                }
+            },
+        }
+
+        problems
+    }
+
+    /// Set the repository local textconv driver for `.gpg` files exactly like `pass git init`
+    /// does. The `.gitattributes` file has to be written and committed separately.
+    pub fn configure_gpg_diff(&self) -> Result<(), GitError> {
+        self.run(&["config", "--local", "diff.gpg.binary", "true"])?;
+        self.run(&["config", "--local", "diff.gpg.textconv", GPG_TEXTCONV])?;
+        Ok(())
+    }
+
+    /// The remote-tracking branch of the current branch, e.g. `refs/remotes/origin/master`, or
+    /// `None` if there is no upstream branch.
+    pub fn upstream(&self) -> Option<String> {
+        self.run(&["rev-parse", "--symbolic-full-name", "@{upstream}"])
+            .ok()
+            .map(|name| name.trim().to_owned())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// The commit `revision` points to, or `None` if it does not exist.
+    pub fn commit_id(&self, revision: &str) -> Option<String> {
+        self.run(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", revision)])
+            .ok()
+            .map(|hash| hash.trim().to_owned())
+            .filter(|hash| !hash.is_empty())
+    }
+
+    /// The commit `HEAD` points to, or `None` if there are no commits yet.
+    pub fn head(&self) -> Option<String> {
+        self.commit_id("HEAD")
+    }
+
+    /// The signatures of the commits of `until` that are not part of `since`, newest first.
+    pub fn commit_signatures(&self, since: &str, until: &str) -> Result<Vec<SignedCommit>, GitError> {
+        let range = format!("{}..{}", since, until);
+        let output = self.run(&[
+            "log",
+            "--name-only",
+            "--format=%x1e%H%x1f%G?%x1f%GK%x1f%an%x1f%s",
+            &range,
+        ])?;
+
+        Ok(output
+            .split('\x1e')
+            .filter(|record| !record.trim().is_empty())
+            .filter_map(|record| {
+                let mut lines = record.lines();
+                let header = lines.next()?.split('\x1f').collect::<Vec<_>>();
+
+                Some(SignedCommit {
+                    hash: header.first()?.to_string(),
+                    status: SignatureStatus::from_code(header.get(1)?),
+                    key: header.get(2).filter(|key| !key.is_empty()).map(|key| key.to_string()),
+                    author: header.get(3)?.to_string(),
+                    subject: header.get(4)?.to_string(),
+                    files: lines
+                        .filter(|line| !line.trim().is_empty())
+                        .map(|line| line.to_owned())
+                        .collect(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process, thread};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    static DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// A fresh directory that is removed again when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let path = env::temp_dir().join(format!(
+                "pass-gtk-git-test-{}-{}",
+                process::id(),
+                DIR_COUNTER.fetch_add(1, Ordering::SeqCst),
+            ));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn bare_remote(dir: &TempDir) -> String {
+        let remote = dir.0.join("remote.git");
+        let status = Command::new("git").args(&["init", "--quiet", "--bare"]).arg(&remote).status().unwrap();
+        assert!(status.success());
+        remote.to_string_lossy().into_owned()
+    }
+
+    fn clone(remote: &str, dir: &TempDir, name: &str) -> GitRepository {
+        let repository = GitRepository::clone_from(remote, &dir.0.join(name)).unwrap();
+        repository.run(&["config", "user.name", "Test"]).unwrap();
+        repository.run(&["config", "user.email", "test@example.com"]).unwrap();
+        repository.run(&["config", "commit.gpgsign", "false"]).unwrap();
+        repository
+    }
+
+    fn commit_file(repository: &GitRepository, name: &str) {
+        let path = repository.path.join(name);
+        fs::write(&path, name).unwrap();
+        repository.commit(&[&path], &format!("Add given password for {} to store.", name)).unwrap();
+    }
+
+    fn wait(process: Result<GitProcess, GitError>) -> Result<(), GitError> {
+        let mut process = process?;
+        loop {
+            if let Some(result) = process.try_finish() {
+                return result;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn sync_through_local_bare_repository() {
+        let dir = TempDir::new();
+        let remote = bare_remote(&dir);
+        let alice = clone(&remote, &dir, "alice");
+        commit_file(&alice, "first.gpg");
+        wait(alice.push()).unwrap();
+
+        let bob = clone(&remote, &dir, "bob");
+        assert!(bob.path.join("first.gpg").is_file());
+        assert_eq!(bob.ahead_behind(), Some((0, 0)));
+
+        commit_file(&alice, "second.gpg");
+        wait(alice.push()).unwrap();
+        wait(bob.pull()).unwrap();
+        assert!(bob.path.join("second.gpg").is_file());
+        assert_eq!(bob.ahead_behind(), Some((0, 0)));
+        assert!(bob.last_fetch().is_some());
+
+        commit_file(&bob, "third.gpg");
+        assert_eq!(bob.ahead_behind(), Some((1, 0)));
+        wait(bob.push()).unwrap();
+        assert_eq!(bob.ahead_behind(), Some((0, 0)));
+    }
+
+    #[test]
+    fn rejected_push_reports_an_error() {
+        let dir = TempDir::new();
+        let remote = bare_remote(&dir);
+        let alice = clone(&remote, &dir, "alice");
+        commit_file(&alice, "first.gpg");
+        wait(alice.push()).unwrap();
+        let bob = clone(&remote, &dir, "bob");
+
+        commit_file(&alice, "second.gpg");
+        wait(alice.push()).unwrap();
+        commit_file(&bob, "third.gpg");
+
+        match wait(bob.push()) {
+            Err(GitError::Command { command, .. }) => assert_eq!(command, "push"),
+            result => panic!("push was not rejected: {:?}", result),
+        }
+
+        wait(bob.pull()).unwrap();
+        assert!(bob.path.join("second.gpg").is_file());
+        assert_eq!(bob.ahead_behind(), Some((1, 0)));
+    }
+
+    #[test]
+    fn signatures_cover_only_pulled_commits() {
+        let dir = TempDir::new();
+        let remote = bare_remote(&dir);
+        let alice = clone(&remote, &dir, "alice");
+        commit_file(&alice, "first.gpg");
+        wait(alice.push()).unwrap();
+        let bob = clone(&remote, &dir, "bob");
+        let upstream = bob.upstream().unwrap();
+        let before_pull = bob.commit_id(&upstream).unwrap();
+
+        commit_file(&alice, "second.gpg");
+        wait(alice.push()).unwrap();
+        commit_file(&bob, "third.gpg");
+        wait(bob.pull()).unwrap();
+
+        let commits = bob.commit_signatures(&before_pull, &upstream).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].files, vec!["second.gpg".to_owned()]);
+        assert_eq!(commits[0].status, SignatureStatus::Unsigned);
+        assert!(upstream.starts_with("refs/remotes/origin/"));
+    }
+}
@@ -2,6 +2,16 @@
 
 mod view;
 mod extensions;
+mod clipboard;
+mod config;
+mod crypto;
+mod deferred;
+mod diff;
+mod error;
+mod generator;
+mod git;
+mod otp;
+mod qr;
 mod app_model;
 mod app_component;
 mod app_view;
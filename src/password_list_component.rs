@@ -1,7 +1,15 @@
 use crate::prelude::*;
-use crate::password_list_model::PasswordListModel;
+use crate::password_list_model::{PasswordListModel, OnboardingChoice, QrContent};
+use crate::crypto::CryptoBackendKind;
+use crate::deferred;
 
 use vgtk::{Component, UpdateAction, Callback, VNode};
+use vgtk::lib::gtk::*;
+
+use std::path::PathBuf;
+
+/// How often a running pull or push is checked, in milliseconds.
+const SYNC_POLL_INTERVAL: u32 = 100;
 
 #[derive(Clone, Debug)]
 pub enum PasswordListMessage {
@@ -9,6 +17,64 @@ pub enum PasswordListMessage {
     Back,
     ShowDirectory(String),
     ShowPassword(String),
+    TogglePasswordVisibility,
+    CopyPassword(String),
+    ClipboardTick,
+    ClearClipboard,
+    CopyOtp(usize),
+    GenerateHotp(usize),
+    AddOtpFromImage,
+    ShowQrCode,
+    SetQrContent(QrContent),
+    SaveQrCode,
+    OtpTick,
+    ShowPreferences,
+    SetCryptoBackend(CryptoBackendKind),
+    SetOnboardingChoice(OnboardingChoice),
+    SetOnboardingPath(String),
+    SetOnboardingKey(String),
+    SetOnboardingGit(bool),
+    SetOnboardingUrl(String),
+    FinishOnboarding,
+    ChooseStore,
+    DismissError,
+    ShowStoreSettings,
+    RepairGpgDiff,
+    NewEntry,
+    SetEditorPath(String),
+    SetEditorForce(bool),
+    EditEntry,
+    SetEditorField(String, String),
+    SaveEntry,
+    ReviewBack,
+    CancelEdit,
+    SetGeneratorLength(usize),
+    SetGeneratorNoSymbols(bool),
+    GeneratePassword,
+    RotatePassword,
+    DeleteEntry(String),
+    StartTransfer(String),
+    SetTransferDestination(String),
+    SetTransferForce(bool),
+    FinishTransfer { copy: bool },
+    StartReencryption(String),
+    SetReencryptionRecipients(String),
+    BeginReencryption,
+    ReencryptionStep,
+    CancelReencryption,
+    CloseReencryption,
+    ShowEntryHistory,
+    SelectRevision(String),
+    RestoreRevision,
+    Pull,
+    Push,
+    Sync,
+    SyncTick,
+    StartMerge(String),
+    SaveMerge,
+    KeepMergeDeleted,
+    TrustCommits,
+    DismissUntrustedCommits,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -73,12 +139,31 @@ impl PasswordListModel {
     pub fn update(&mut self, message: PasswordListMessage) {
         match message {
             PasswordListMessage::Reload => {
-                self.reload_passwords();
+                self.reload_keeping_navigation();
             },
             PasswordListMessage::Back => {
                 if self.can_go_back() {
-                    if self.current_password.is_some() {
-                        self.current_password = None;
+                    if self.show_preferences {
+                        self.show_preferences = false;
+                    } else if self.onboarding.is_some() {
+                        self.onboarding = None;
+                    } else if self.store_settings.is_some() {
+                        self.store_settings = None;
+                    } else if self.editor.is_some() {
+                        self.editor = None;
+                    } else if self.transfer.is_some() {
+                        self.transfer = None;
+                    } else if self.reencryption.is_some() {
+                        self.cancel_reencryption();
+                        self.reencryption = None;
+                    } else if self.entry_history.is_some() {
+                        self.entry_history = None;
+                    } else if self.merge.is_some() {
+                        self.merge = None;
+                    } else if self.qr_view.is_some() {
+                        self.qr_view = None;
+                    } else if self.current_password.is_some() {
+                        self.hide_password();
                     } else {
                         self.history.pop();
                     }
@@ -89,11 +174,334 @@ impl PasswordListModel {
                     .split("/")
                     .map(|s| s.to_owned())
                     .collect();
-                self.current_password = None;
+                self.close_pages();
+                self.hide_password();
             },
             PasswordListMessage::ShowPassword(password) => {
-                self.current_password = Some(password);
+                self.close_pages();
+                self.show_password(password);
+            },
+            PasswordListMessage::TogglePasswordVisibility => {
+                self.password_visible = !self.password_visible;
+            },
+            PasswordListMessage::CopyPassword(password) => {
+                if self.copy_password(password) {
+                    deferred::activate_action_later("clipboard-tick", 1000);
+                }
+            },
+            PasswordListMessage::ClipboardTick => {
+                if self.clipboard_tick() {
+                    deferred::activate_action_later("clipboard-tick", 1000);
+                }
+            },
+            PasswordListMessage::ClearClipboard => {
+                self.restore_clipboard();
+            },
+            PasswordListMessage::CopyOtp(index) => {
+                if self.copy_otp(index) {
+                    deferred::activate_action_later("clipboard-tick", 1000);
+                }
+            },
+            PasswordListMessage::GenerateHotp(index) => {
+                self.generate_hotp(index);
+            },
+            PasswordListMessage::AddOtpFromImage => {
+                if let Some(image) = choose_otp_image() {
+                    self.add_otp_from_image(&image);
+                }
+            },
+            PasswordListMessage::ShowQrCode => {
+                self.show_qr_code();
+            },
+            PasswordListMessage::SetQrContent(content) => {
+                self.set_qr_content(content);
+            },
+            PasswordListMessage::SaveQrCode => {
+                let name = self.current_password
+                    .as_ref()
+                    .and_then(|password| password.split('/').last())
+                    .unwrap_or("qr-code")
+                    .to_owned();
+                if let Some(path) = choose_qr_code_file(&name) {
+                    self.save_qr_code(path);
+                }
+            },
+            PasswordListMessage::OtpTick => {
+                if self.otp_tick() {
+                    deferred::activate_action_later("otp-tick", 1000);
+                }
+            },
+            PasswordListMessage::ShowPreferences => {
+                self.show_preferences = true;
+            },
+            PasswordListMessage::SetCryptoBackend(kind) => {
+                self.set_crypto_backend(kind);
+            },
+            PasswordListMessage::SetOnboardingChoice(choice) => {
+                if let Some(ref mut onboarding) = self.onboarding {
+                    onboarding.choice = choice;
+                }
+            },
+            PasswordListMessage::SetOnboardingPath(path) => {
+                if let Some(ref mut onboarding) = self.onboarding {
+                    onboarding.path = path;
+                }
+            },
+            PasswordListMessage::SetOnboardingKey(fingerprint) => {
+                if let Some(ref mut onboarding) = self.onboarding {
+                    onboarding.key = Some(fingerprint);
+                }
+            },
+            PasswordListMessage::SetOnboardingGit(use_git) => {
+                if let Some(ref mut onboarding) = self.onboarding {
+                    onboarding.use_git = use_git;
+                }
+            },
+            PasswordListMessage::SetOnboardingUrl(url) => {
+                if let Some(ref mut onboarding) = self.onboarding {
+                    onboarding.url = url;
+                }
+            },
+            PasswordListMessage::FinishOnboarding => {
+                self.finish_onboarding();
+            },
+            PasswordListMessage::ChooseStore => {
+                self.choose_store();
+            },
+            PasswordListMessage::DismissError => {
+                self.error = None;
+            },
+            PasswordListMessage::ShowStoreSettings => {
+                self.show_preferences = false;
+                self.show_store_settings();
+            },
+            PasswordListMessage::RepairGpgDiff => {
+                self.repair_gpg_diff();
+            },
+            PasswordListMessage::NewEntry => {
+                self.new_entry();
+            },
+            PasswordListMessage::SetEditorPath(path) => {
+                if let Some(ref mut editor) = self.editor {
+                    editor.path = path;
+                }
+            },
+            PasswordListMessage::EditEntry => {
+                self.edit_entry();
+            },
+            PasswordListMessage::SetEditorField(key, value) => {
+                if let Some(ref mut editor) = self.editor {
+                    editor.set_field(&key, &value);
+                }
+            },
+            PasswordListMessage::SaveEntry => {
+                self.save_editor();
+            },
+            PasswordListMessage::ReviewBack => {
+                if let Some(ref mut editor) = self.editor {
+                    editor.review = false;
+                }
+            },
+            PasswordListMessage::SetEditorForce(force) => {
+                if let Some(ref mut editor) = self.editor {
+                    editor.force = force;
+                }
+            },
+            PasswordListMessage::CancelEdit => {
+                self.editor = None;
+            },
+            PasswordListMessage::SetGeneratorLength(length) => {
+                self.generator.length = length;
+            },
+            PasswordListMessage::SetGeneratorNoSymbols(no_symbols) => {
+                self.generator.no_symbols = no_symbols;
+            },
+            PasswordListMessage::GeneratePassword => {
+                self.generate_in_editor();
+            },
+            PasswordListMessage::RotatePassword => {
+                self.rotate_password();
             },
+            PasswordListMessage::DeleteEntry(path) => {
+                let name = path.trim_start_matches("./").to_owned();
+                let entries = self.entries_to_delete(&path);
+                if confirm_delete(&name, &entries) {
+                    if let Err(err) = self.delete_entry(&path) {
+                        self.error = Some(err);
+                    }
+                }
+            },
+            PasswordListMessage::StartTransfer(path) => {
+                self.start_transfer(path);
+            },
+            PasswordListMessage::SetTransferDestination(destination) => {
+                if let Some(ref mut transfer) = self.transfer {
+                    transfer.destination = destination;
+                }
+            },
+            PasswordListMessage::SetTransferForce(force) => {
+                if let Some(ref mut transfer) = self.transfer {
+                    transfer.force = force;
+                }
+            },
+            PasswordListMessage::FinishTransfer { copy } => {
+                self.finish_transfer(copy);
+            },
+            PasswordListMessage::StartReencryption(folder) => {
+                self.start_reencryption(folder);
+            },
+            PasswordListMessage::SetReencryptionRecipients(recipients) => {
+                if let Some(ref mut reencryption) = self.reencryption {
+                    reencryption.recipients_text = recipients;
+                }
+            },
+            PasswordListMessage::BeginReencryption => {
+                if self.begin_reencryption() {
+                    deferred::activate_action_later("reencryption-step", 0);
+                }
+            },
+            PasswordListMessage::ReencryptionStep => {
+                if self.reencryption_step() {
+                    deferred::activate_action_later("reencryption-step", 0);
+                }
+            },
+            PasswordListMessage::CancelReencryption => {
+                self.cancel_reencryption();
+            },
+            PasswordListMessage::CloseReencryption => {
+                self.reencryption = None;
+            },
+            PasswordListMessage::ShowEntryHistory => {
+                self.show_entry_history();
+            },
+            PasswordListMessage::SelectRevision(hash) => {
+                self.select_revision(hash);
+            },
+            PasswordListMessage::RestoreRevision => {
+                self.restore_revision();
+            },
+            PasswordListMessage::Pull => {
+                if self.start_sync(true, false) {
+                    deferred::activate_action_later("sync-tick", SYNC_POLL_INTERVAL);
+                }
+            },
+            PasswordListMessage::Push => {
+                if self.start_sync(false, true) {
+                    deferred::activate_action_later("sync-tick", SYNC_POLL_INTERVAL);
+                }
+            },
+            PasswordListMessage::Sync => {
+                if self.start_sync(true, true) {
+                    deferred::activate_action_later("sync-tick", SYNC_POLL_INTERVAL);
+                }
+            },
+            PasswordListMessage::SyncTick => {
+                if self.sync_tick() {
+                    deferred::activate_action_later("sync-tick", SYNC_POLL_INTERVAL);
+                }
+            },
+            PasswordListMessage::StartMerge(password) => {
+                self.start_merge(password);
+            },
+            PasswordListMessage::SaveMerge => {
+                self.save_merge();
+            },
+            PasswordListMessage::KeepMergeDeleted => {
+                self.keep_merge_deleted();
+            },
+            PasswordListMessage::TrustCommits => {
+                self.trust_commits();
+            },
+            PasswordListMessage::DismissUntrustedCommits => {
+                self.untrusted_commits = vec![];
+            },
+        }
+
+        // keep the TOTP codes of a newly shown entry up to date
+        if self.start_otp_countdown() {
+            deferred::activate_action_later("otp-tick", 1000);
         }
     }
 }
+
+/// Ask the user whether the listed entries should really be deleted.
+fn confirm_delete(name: &str, entries: &[String]) -> bool {
+    let dialog = MessageDialog::new(
+        vgtk::current_window().as_ref(),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        MessageType::Warning,
+        ButtonsType::None,
+        &format!("Delete “{}”?", name),
+    );
+    let secondary_text = if entries.is_empty() {
+        "This folder does not contain any passwords.".to_owned()
+    } else {
+        format!("The following entries will be removed permanently:\n\n{}", entries.join("\n"))
+    };
+    dialog.set_property_secondary_text(Some(&secondary_text));
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog
+        .add_button("Delete", ResponseType::Accept)
+        .get_style_context()
+        .add_class("destructive-action");
+
+    let response = dialog.run();
+    dialog.close();
+
+    response == ResponseType::Accept
+}
+
+/// Ask the user for a screenshot of a QR code to read a one-time password from.
+fn choose_otp_image() -> Option<PathBuf> {
+    let chooser = FileChooserNative::new(
+        Some("Add OTP from image"),
+        vgtk::current_window().as_ref(),
+        FileChooserAction::Open,
+        Some("Add"),
+        Some("Cancel"),
+    );
+    let filter = FileFilter::new();
+    filter.set_name(Some("PNG and JPEG images"));
+    filter.add_mime_type("image/png");
+    filter.add_mime_type("image/jpeg");
+    chooser.add_filter(&filter);
+
+    let response = chooser.run();
+    let image = chooser.get_filename();
+    chooser.destroy();
+
+    if response == ResponseType::Accept {
+        image
+    } else {
+        None
+    }
+}
+
+/// Ask the user where to save a QR code. The format is chosen by the file extension.
+fn choose_qr_code_file(name: &str) -> Option<PathBuf> {
+    let chooser = FileChooserNative::new(
+        Some("Save QR code"),
+        vgtk::current_window().as_ref(),
+        FileChooserAction::Save,
+        Some("Save"),
+        Some("Cancel"),
+    );
+    chooser.set_do_overwrite_confirmation(true);
+    chooser.set_current_name(&format!("{}.png", name));
+    for (name, mime_type) in &[("PNG image", "image/png"), ("SVG image", "image/svg+xml")] {
+        let filter = FileFilter::new();
+        filter.set_name(Some(name));
+        filter.add_mime_type(mime_type);
+        chooser.add_filter(&filter);
+    }
+
+    let response = chooser.run();
+    let path = chooser.get_filename();
+    chooser.destroy();
+
+    if response == ResponseType::Accept {
+        path
+    } else {
+        None
+    }
+}
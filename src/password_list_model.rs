@@ -1,90 +1,2816 @@
-use id_tree::{Tree, Node, NodeId, InsertBehavior};
+use crate::clipboard;
+use crate::config::Config;
+use crate::crypto::{CryptoBackend, CryptoBackendKind, Key, Signer};
+use crate::diff::{self, DiffLine};
+use crate::error::Error;
+use crate::generator::{self, GeneratorOptions};
+use crate::git::{self, Commit, GitProcess, GitRepository, SignedCommit};
+use crate::otp::{self, Otp, OtpError, OtpKind};
+use crate::qr;
+
+use id_tree::{Tree, Node, NodeId, InsertBehavior, RemoveBehavior};
 use directories::{BaseDirs};
+use vgtk::lib::gtk::{TextBuffer, TextBufferExt, TextTagTable};
+
+use std::{io, fs, env, fmt, process};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::cmp::Ordering;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The effective `.gpg-id` recipients of a directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recipients {
+    pub keys: Vec<String>,
+    /// The directory whose `.gpg-id` defines the recipients, e.g. `./team`, or `None` if the
+    /// store has not been initialized.
+    pub source: Option<String>,
+}
+
+impl Recipients {
+    /// Whether the recipients of the directory at `path` are inherited from a parent directory.
+    pub fn is_inherited_at(&self, path: &str) -> bool {
+        self.source.as_ref().map(|source| source != path).unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Entry {
+    Password(String),
+    Directory(String, Recipients),
+}
+
+impl Entry {
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::Password(name) => name,
+            Entry::Directory(name, _) => name,
+        }
+    }
+
+    pub fn is_directory(&self) -> bool {
+        match self {
+            Entry::Password(_) => false,
+            Entry::Directory(_, _) => true,
+        }
+    }
+}
+
+fn compare_entries(a: &Entry, b: &Entry) -> Ordering {
+    match (a.is_directory(), b.is_directory()) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        _ => a.name().to_lowercase().cmp(&b.name().to_lowercase()),
+    }
+}
+
+/// The state of the entry editor page.
+#[derive(Clone, Debug)]
+pub struct Editor {
+    /// The path of the entry relative to the password store, without the `.gpg` extension.
+    pub path: String,
+    pub buffer: TextBuffer,
+    /// The decrypted content before editing, if an existing entry is edited.
+    pub original: Option<String>,
+    /// Whether the diff between the original and the edited content is shown before saving.
+    pub review: bool,
+    /// Whether saving a new entry replaces an existing entry with the same name.
+    pub force: bool,
+    pub error: Option<String>,
+}
+
+impl Editor {
+    pub fn new(path: String, content: &str) -> Self {
+        let buffer = TextBuffer::new(None::<&TextTagTable>);
+        buffer.set_text(content);
+
+        Self {
+            path,
+            buffer,
+            original: None,
+            review: false,
+            force: false,
+            error: None,
+        }
+    }
+
+    pub fn edit(path: String, content: &str) -> Self {
+        Self {
+            original: Some(content.to_owned()),
+            ..Self::new(path, content)
+        }
+    }
+
+    pub fn content(&self) -> EntryContent {
+        EntryContent::parse(&self.text())
+    }
+
+    /// Replace the password line of the edited content, leaving all other lines untouched.
+    pub fn set_password(&mut self, password: String) {
+        let mut content = self.content();
+        content.password = password;
+        self.buffer.set_text(&content.to_string());
+    }
+
+    /// Change a single field of the edited content, leaving all other lines untouched.
+    pub fn set_field(&mut self, key: &str, value: &str) {
+        let mut content = self.content();
+        if content.field(key) == Some(value) {
+            return;
+        }
+
+        content.set_field(key, value);
+        self.buffer.set_text(&content.to_string());
+    }
+
+    pub fn diff(&self) -> Vec<DiffLine> {
+        diff::diff_lines(self.original.as_ref().map(|original| original.as_str()).unwrap_or(""), &self.text())
+    }
+
+    pub fn text(&self) -> String {
+        self.buffer
+            .get_text(&self.buffer.get_start_iter(), &self.buffer.get_end_iter(), false)
+            .map(|text| text.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// A line following the password line of a decrypted entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntryLine {
+    /// A browserpass-style `key: value` field. The separator keeps the exact whitespace after the
+    /// colon, so the line can be written back unchanged.
+    Field {
+        key: String,
+        separator: String,
+        value: String,
+    },
+    /// An `otpauth://` URI as stored by pass-otp.
+    OtpUri(String),
+    /// Any other free-form line.
+    Note(String),
+}
+
+impl EntryLine {
+    fn parse(line: &str) -> Self {
+        if line.trim_start().starts_with("otpauth://") {
+            return EntryLine::OtpUri(line.to_owned());
+        }
+
+        if let Some(colon) = line.find(':') {
+            let key = &line[..colon];
+            let rest = &line[colon + 1..];
+            let is_key = !key.is_empty()
+                && key.trim() == key
+                && !key.contains(char::is_whitespace)
+                && !rest.starts_with("//");
+
+            if is_key {
+                let value_start = rest.len() - rest.trim_start_matches(|c| c == ' ' || c == '\t').len();
+                return EntryLine::Field {
+                    key: key.to_owned(),
+                    separator: format!(":{}", &rest[..value_start]),
+                    value: rest[value_start..].to_owned(),
+                };
+            }
+        }
+
+        EntryLine::Note(line.to_owned())
+    }
+}
+
+impl fmt::Display for EntryLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntryLine::Field { key, separator, value } => write!(f, "{}{}{}", key, separator, value),
+            EntryLine::OtpUri(uri) => write!(f, "{}", uri),
+            EntryLine::Note(note) => write!(f, "{}", note),
+        }
+    }
+}
+
+/// Escape the special characters of a WiFi join string field.
+fn escape_wifi(value: &str) -> String {
+    value.chars().fold(String::new(), |mut escaped, c| {
+        if "\\;,:\"".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        escaped
+    })
+}
+
+const LOGIN_KEYS: &[&str] = &["login", "username", "user"];
+const URL_KEYS: &[&str] = &["url", "link", "website", "web", "site"];
+
+/// The decrypted content of a password entry, split up following the `pass` conventions: the
+/// first line is the password, followed by `key: value` fields, `otpauth://` URIs and notes.
+///
+/// Serializing an unmodified `EntryContent` yields exactly the content it was parsed from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryContent {
+    pub password: String,
+    pub lines: Vec<EntryLine>,
+    trailing_newline: bool,
+}
+
+impl EntryContent {
+    pub fn parse(content: &str) -> Self {
+        let trailing_newline = content.ends_with('\n');
+        let content = if trailing_newline { &content[..content.len() - 1] } else { content };
+        let mut lines = content.split('\n');
+
+        Self {
+            password: lines.next().unwrap_or("").to_owned(),
+            lines: lines.map(EntryLine::parse).collect(),
+            trailing_newline,
+        }
+    }
+
+    /// The value of the first field named `key`, compared case-insensitively.
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            EntryLine::Field { key: field_key, value, .. } if field_key.eq_ignore_ascii_case(key) => {
+                Some(value.trim_end())
+            },
+            _ => None,
+        })
+    }
+
+    /// Set the value of the field named `key`, appending a new field if there is none yet.
+    pub fn set_field(&mut self, key: &str, new_value: &str) {
+        for line in self.lines.iter_mut() {
+            if let EntryLine::Field { key: field_key, value, .. } = line {
+                if field_key.eq_ignore_ascii_case(key) {
+                    *value = new_value.to_owned();
+                    return;
+                }
+            }
+        }
+
+        self.lines.push(EntryLine::Field {
+            key: key.to_owned(),
+            separator: ": ".into(),
+            value: new_value.to_owned(),
+        });
+    }
+
+    pub fn fields(&self) -> Vec<(&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            EntryLine::Field { key, value, .. } => Some((key.as_str(), value.trim_end())),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn login(&self) -> Option<&str> {
+        LOGIN_KEYS.iter().find_map(|key| self.field(key))
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        URL_KEYS.iter().find_map(|key| self.field(key))
+    }
+
+    /// The fields besides the ones `login` and `url` are read from.
+    pub fn other_fields(&self) -> Vec<(&str, &str)> {
+        let login_key = LOGIN_KEYS.iter().find(|key| self.field(key).is_some());
+        let url_key = URL_KEYS.iter().find(|key| self.field(key).is_some());
+
+        self.fields()
+            .into_iter()
+            .filter(|(key, _)| !login_key.into_iter().chain(url_key).any(|known| known.eq_ignore_ascii_case(key)))
+            .collect()
+    }
+
+    /// All `otpauth://` URIs of this entry. pass-otp also allows the URI to be the first line.
+    pub fn otp_uris(&self) -> Vec<&str> {
+        let password = Some(self.password.trim())
+            .filter(|password| password.starts_with("otpauth://"));
+
+        password.into_iter().chain(self.lines.iter().filter_map(|line| match line {
+            EntryLine::OtpUri(uri) => Some(uri.trim()),
+            _ => None,
+        })).collect()
+    }
+
+    /// Replace the `index`th URI as returned by `otp_uris`.
+    pub fn replace_otp_uri(&mut self, index: usize, uri: String) {
+        let in_first_line = self.password.trim().starts_with("otpauth://");
+        if in_first_line && index == 0 {
+            self.password = uri;
+            return;
+        }
+
+        let index = if in_first_line { index - 1 } else { index };
+        if let Some(line) = self.lines.iter_mut().filter(|line| matches!(line, EntryLine::OtpUri(_))).nth(index) {
+            *line = EntryLine::OtpUri(uri);
+        }
+    }
+
+    /// A WiFi join string as phone cameras understand it, built from the `ssid:`, `security:`
+    /// and `hidden:` fields and the password.
+    pub fn wifi(&self) -> Option<String> {
+        let ssid = self.field("ssid").filter(|ssid| !ssid.is_empty())?;
+        let security = match self.field("security").map(|security| security.to_lowercase()) {
+            Some(ref security) if ["none", "nopass", "open"].contains(&security.as_str()) => "nopass",
+            Some(ref security) if security.contains("wep") => "WEP",
+            _ => "WPA",
+        };
+        let hidden = match self.field("hidden").map(|hidden| hidden.to_lowercase()) {
+            Some(ref hidden) if hidden == "true" || hidden == "yes" => "H:true;",
+            _ => "",
+        };
+        let password = match security {
+            "nopass" => String::new(),
+            _ => format!("P:{};", escape_wifi(&self.password)),
+        };
+
+        Some(format!("WIFI:T:{};S:{};{}{};", security, escape_wifi(ssid), password, hidden))
+    }
+
+    pub fn notes(&self) -> String {
+        self.lines.iter().filter_map(|line| match line {
+            EntryLine::Note(note) => Some(note.trim_end()),
+            _ => None,
+        }).collect::<Vec<_>>().join("\n").trim().to_owned()
+    }
+}
+
+impl fmt::Display for EntryContent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.password)?;
+        for line in &self.lines {
+            write!(f, "\n{}", line)?;
+        }
+        if self.trailing_newline {
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The state of the move/copy page.
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    /// The password or directory path of the entry to move or copy, e.g. `./email/example.com`.
+    pub source: String,
+    /// The destination relative to the password store, like `pass mv` accepts it.
+    pub destination: String,
+    pub force: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReencryptionState {
+    /// The new recipients are being entered.
+    Editing,
+    /// Entries are re-encrypted into temporary files one by one.
+    Running,
+    /// All entries were re-encrypted and the new `.gpg-id` is in place.
+    Finished,
+    /// The re-encryption was cancelled or failed and all temporary files were removed.
+    Aborted,
+}
+
+/// The state of a `pass init -p <folder>` run, which replaces the `.gpg-id` of a folder and
+/// re-encrypts every entry underneath to the new recipients.
+///
+/// Entries are encrypted to temporary files first. Only after every entry succeeded, the new
+/// `.gpg-id` is written and the temporary files replace the entries, so a cancelled or failed
+/// run never leaves entries encrypted to different recipients behind.
+#[derive(Clone, Debug)]
+pub struct Reencryption {
+    /// The folder to initialize, e.g. `./team`.
+    pub folder: String,
+    pub recipients_text: String,
+    pub recipients: Vec<String>,
+    /// The password paths of all entries governed by the `.gpg-id` of `folder`.
+    pub entries: Vec<String>,
+    /// The entries already encrypted into a temporary file.
+    pub staged: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    /// The signature of the new `.gpg-id`, if `PASSWORD_STORE_SIGNING_KEY` is set.
+    pub signature: Option<Vec<u8>>,
+    pub state: ReencryptionState,
+    pub error: Option<String>,
+}
+
+impl Reencryption {
+    pub fn progress(&self) -> f64 {
+        if self.entries.is_empty() {
+            1.0
+        } else {
+            (self.staged.len() + self.failed.len()) as f64 / self.entries.len() as f64
+        }
+    }
+
+    pub fn current_entry(&self) -> Option<&String> {
+        self.entries.get(self.staged.len() + self.failed.len())
+    }
+}
+
+/// The state of the history page of an entry.
+#[derive(Clone, Debug)]
+pub struct EntryHistory {
+    pub password: String,
+    pub commits: Vec<Commit>,
+    /// The hash of the selected commit.
+    pub selected: Option<String>,
+    /// The decrypted content of the entry in the selected commit.
+    pub selected_content: Option<String>,
+    pub diff: Vec<DiffLine>,
+    pub error: Option<String>,
+}
+
+/// The state of the password store repository compared to its remote.
+#[derive(Clone, Debug, Default)]
+pub struct SyncStatus {
+    /// The commits ahead and behind of the upstream branch, if there is one.
+    pub ahead_behind: Option<(usize, usize)>,
+    pub last_sync: Option<SystemTime>,
+    pub error: Option<String>,
+    pub job: Option<SyncJob>,
+}
+
+/// A pull or push running in the background, polled by the `sync-tick` action.
+#[derive(Clone, Debug)]
+pub struct SyncJob {
+    pub pulling: bool,
+    /// Whether a push follows once the pull succeeded.
+    pub push_after_pull: bool,
+    /// The remote-tracking branch a pull fetches into, to verify the commits it brought in.
+    pub upstream: Option<String>,
+    process: Rc<RefCell<GitProcess>>,
+}
+
+/// How the password store is set up on the first run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnboardingChoice {
+    /// Create a new store encrypted for one of the user's keys, like `pass init`.
+    Create,
+    /// Clone an existing store with `git clone`.
+    Clone,
+    /// Use an existing store in another directory.
+    Existing,
+}
+
+impl OnboardingChoice {
+    pub fn name(self) -> &'static str {
+        match self {
+            OnboardingChoice::Create => "create",
+            OnboardingChoice::Clone => "clone",
+            OnboardingChoice::Existing => "existing",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "create" => Some(OnboardingChoice::Create),
+            "clone" => Some(OnboardingChoice::Clone),
+            "existing" => Some(OnboardingChoice::Existing),
+            _ => None,
+        }
+    }
+}
+
+/// The state of the first-run assistant shown while there is no password store.
+#[derive(Clone, Debug)]
+pub struct Onboarding {
+    pub choice: OnboardingChoice,
+    /// The directory of the new or cloned store, or the existing store to use.
+    pub path: String,
+    /// The secret keys a new store can be encrypted for.
+    pub keys: Vec<Key>,
+    /// The fingerprint of the key a new store is encrypted for.
+    pub key: Option<String>,
+    /// Track the new store with git, like `pass git init`.
+    pub use_git: bool,
+    /// The path or URL of the repository to clone.
+    pub url: String,
+    pub error: Option<String>,
+}
+
+/// A secret copied to the clipboard, which is cleared again after `PASSWORD_STORE_CLIP_TIME`.
+#[derive(Clone, Debug)]
+pub struct ClipboardSecret {
+    /// The password path of the entry the secret belongs to.
+    pub password: String,
+    pub secret: String,
+    /// The clipboard content before the first secret was copied.
+    pub previous: Option<String>,
+    /// Whether the previous content was a secret itself, e.g. copied from another password manager.
+    pub previous_is_secret: bool,
+    pub copied_at: Instant,
+    pub duration: Duration,
+}
+
+impl ClipboardSecret {
+    pub fn remaining(&self) -> Duration {
+        self.duration.checked_sub(self.copied_at.elapsed()).unwrap_or_default()
+    }
+}
+
+/// The state of the settings page of the password store.
+#[derive(Clone, Debug, Default)]
+pub struct StoreSettings {
+    /// Whether the password store is a git repository.
+    pub has_git: bool,
+    /// What is missing for `git log -p` to show decrypted diffs of entries.
+    pub gpg_diff_problems: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// What the QR code of an entry encodes, like `pass show --qrcode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QrContent {
+    Password,
+    /// The `index`th `otpauth://` URI of the entry.
+    Otp(usize),
+    /// A WiFi join string, see `EntryContent::wifi`.
+    Wifi,
+}
+
+impl QrContent {
+    pub fn name(self) -> String {
+        match self {
+            QrContent::Password => "password".into(),
+            QrContent::Otp(index) => format!("otp:{}", index),
+            QrContent::Wifi => "wifi".into(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "password" => Some(QrContent::Password),
+            "wifi" => Some(QrContent::Wifi),
+            name => name.strip_prefix("otp:")?.parse().ok().map(QrContent::Otp),
+        }
+    }
+}
+
+/// The state of the QR code page of the shown entry.
+#[derive(Clone, Debug)]
+pub struct QrView {
+    pub content: QrContent,
+    /// The text encoded in the QR code, if `content` can be encoded.
+    pub data: Option<String>,
+    pub error: Option<String>,
+}
+
+/// The state of the three-way merge editor for an entry with a merge conflict.
+#[derive(Clone, Debug)]
+pub struct MergeEditor {
+    pub password: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub ours_label: String,
+    pub theirs_label: String,
+    /// The merge result, pre-filled with all non-conflicting changes.
+    pub buffer: TextBuffer,
+    /// Which side deleted the entry while the other one changed it, e.g. `remote`.
+    pub deleted_by: Option<String>,
+    /// Why the versions could not be decrypted. The entry cannot be saved then.
+    pub error: Option<String>,
+    pub save_error: Option<String>,
+}
+
+impl MergeEditor {
+    pub fn text(&self) -> String {
+        self.buffer
+            .get_text(&self.buffer.get_start_iter(), &self.buffer.get_end_iter(), false)
+            .map(|text| text.to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PasswordListModel {
+    pub passwords: Tree<Entry>,
+    pub password_store_path: PathBuf,
+    pub config: Config,
+    pub crypto: Rc<dyn CryptoBackend>,
+    /// The keys `.gpg-id` files have to be signed with, read from `PASSWORD_STORE_SIGNING_KEY`.
+    pub signing_keys: Vec<String>,
+    pub show_preferences: bool,
+    pub onboarding: Option<Onboarding>,
+    pub store_settings: Option<StoreSettings>,
+    pub history: Vec<String>,
+    pub current_password: Option<String>,
+    pub current_content: Option<EntryContent>,
+    pub password_visible: bool,
+    pub password_error: Option<String>,
+    pub editor: Option<Editor>,
+    pub transfer: Option<Transfer>,
+    pub reencryption: Option<Reencryption>,
+    pub entry_history: Option<EntryHistory>,
+    pub sync: Option<SyncStatus>,
+    /// The password paths of entries with unresolved merge conflicts.
+    pub conflicts: Vec<String>,
+    pub merge: Option<MergeEditor>,
+    pub qr_view: Option<QrView>,
+    /// Pulled commits since the trusted commit of the store that are not signed by a trusted key.
+    pub untrusted_commits: Vec<SignedCommit>,
+    /// The remote commit up to which `untrusted_commits` were checked. It becomes the trusted
+    /// commit once the user trusts them.
+    pub untrusted_until: Option<String>,
+    pub clipboard: Option<ClipboardSecret>,
+    /// Whether the `clipboard-tick` action is scheduled.
+    pub clipboard_countdown: bool,
+    /// Whether the `otp-tick` action is scheduled to update the shown TOTP codes.
+    pub otp_countdown: bool,
+    /// The index and code of the last generated HOTP code of the shown entry.
+    pub hotp_code: Option<(usize, String)>,
+    pub otp_error: Option<String>,
+    pub generator: GeneratorOptions,
+    pub error: Option<Error>,
+}
+
+impl Default for PasswordListModel {
+    fn default() -> Self {
+        let config = Config::load();
+        let password_store_path = default_store_path(&config).unwrap_or_default();
+        let crypto = config.crypto_backend.create();
+
+        Self::new(config, crypto, password_store_path)
+    }
+}
+
+impl PasswordListModel {
+    fn new(config: Config, crypto: Rc<dyn CryptoBackend>, password_store_path: PathBuf) -> Self {
+        let mut me = Self {
+            passwords: Tree::new(),
+            password_store_path,
+            config,
+            crypto,
+            signing_keys: signing_keys(),
+            show_preferences: false,
+            onboarding: None,
+            store_settings: None,
+            history: vec![".".into()],
+            current_password: None,
+            current_content: None,
+            password_visible: false,
+            password_error: None,
+            editor: None,
+            transfer: None,
+            reencryption: None,
+            entry_history: None,
+            sync: None,
+            conflicts: vec![],
+            merge: None,
+            qr_view: None,
+            untrusted_commits: vec![],
+            untrusted_until: None,
+            clipboard: None,
+            clipboard_countdown: false,
+            otp_countdown: false,
+            hotp_code: None,
+            otp_error: None,
+            generator: GeneratorOptions::default(),
+            error: None,
+        };
+        me.reload_passwords();
+
+        me
+    }
+
+    /// A model of the store at `path` that uses `crypto` and the default configuration, so tests
+    /// neither need a keyring nor touch the configuration of the user.
+    #[cfg(test)]
+    pub fn with_backend(crypto: Rc<dyn CryptoBackend>, path: &Path) -> Self {
+        Self::new(Config::default(), crypto, path.to_owned())
+    }
+}
+
+/// The password store to show: `PASSWORD_STORE_DIR` if it exists, the store chosen during
+/// onboarding, or where `pass` would create a new store.
+fn default_store_path(config: &Config) -> Option<PathBuf> {
+    let env_path = env::var_os("PASSWORD_STORE_DIR").map(PathBuf::from);
+
+    env_path
+        .clone()
+        .filter(|path| path.is_dir())
+        .or_else(|| config.store_path.clone())
+        .or(env_path)
+        .or_else(|| BaseDirs::new().map(|base_dirs| base_dirs.home_dir().join(".password-store")))
+}
+
+/// Replace a leading `~` with the home directory of the user.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~"), BaseDirs::new()) {
+        (Some(rest), Some(base_dirs)) if rest.is_empty() || rest.starts_with('/') => {
+            base_dirs.home_dir().join(rest.trim_start_matches('/'))
+        },
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Initialize a new password store at `path` encrypted for `key`, like `pass init` followed by
+/// `pass git init` if `use_git` is set.
+fn create_store(
+    crypto: &dyn CryptoBackend,
+    signing_keys: &[String],
+    path: &Path,
+    key: Option<&str>,
+    use_git: bool,
+) -> io::Result<()> {
+    let key = key.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Choose a key to encrypt the password store for"))?;
+    let gpg_id = path.join(".gpg-id");
+    if gpg_id.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("There already is a password store at {}", path.display()),
+        ));
+    }
+
+    let content = format!("{}\n", key);
+    let signature = sign_gpg_id(crypto, content.as_bytes(), signing_keys)?;
+
+    fs::create_dir_all(path)?;
+    write_atomically(&gpg_id, content.as_bytes())?;
+    if let Some(signature) = signature {
+        write_atomically(&path.join(".gpg-id.sig"), &signature)?;
+    }
+
+    if use_git {
+        let git = GitRepository::init(path)
+            .and_then(|git| git.commit(&[path], "Add current contents of password store.").map(|_| git))
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+        configure_gpg_diff(&git, path)?;
+    }
+
+    Ok(())
+}
+
+/// Add the `.gpg` diff attribute to `.gitattributes`, configure the textconv driver and commit the
+/// change, exactly like `pass git init` does.
+fn configure_gpg_diff(git: &GitRepository, store_path: &Path) -> io::Result<()> {
+    let attributes_path = store_path.join(".gitattributes");
+    let mut attributes = match fs::read_to_string(&attributes_path) {
+        Ok(attributes) => attributes,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    if !git::has_gpg_diff_attribute(&attributes) {
+        if !attributes.is_empty() && !attributes.ends_with('\n') {
+            attributes.push('\n');
+        }
+        attributes.push_str(git::GPG_DIFF_ATTRIBUTE);
+        attributes.push('\n');
+        write_atomically(&attributes_path, attributes.as_bytes())?;
+    }
+
+    git.configure_gpg_diff()
+        .and_then(|_| git.commit(&[&attributes_path], "Configure git repository for gpg file diff."))
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+}
+
+/// Check that `path` names an entry inside of the password store and turn it into a password
+/// path as used by `current_password`.
+fn validate_entry_path(path: &str) -> io::Result<String> {
+    let path = path.trim().trim_start_matches("./").trim_matches('/');
+    let is_valid = !path.is_empty()
+        && path.split('/').all(|component| !component.is_empty() && component != "." && component != "..");
+
+    if is_valid {
+        Ok(format!("./{}", path))
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' is not a valid entry name", path)))
+    }
+}
+
+/// Write `content` to a temporary file next to `path` and rename it over `path` afterwards, so
+/// `path` either has its old or its new content, but is never truncated.
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let file_name = path.file_name().unwrap_or("..".as_ref()).to_string_lossy();
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let tmp_path = dir.join(format!(".{}.{}.tmp", file_name, process::id()));
+
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    } else if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+
+    result
+}
+
+/// The recipients listed in the `.gpg-id` of `dir`, if there is one. Comments and empty lines
+/// are ignored like `pass` does.
+fn read_gpg_id(dir: &Path) -> io::Result<Option<Vec<String>>> {
+    let gpg_id = dir.join(".gpg-id");
+    if !gpg_id.is_file() {
+        return Ok(None);
+    }
+
+    Ok(Some(fs::read_to_string(gpg_id)?
+        .lines()
+        .map(|line| line.split('#').next().unwrap().trim().to_owned())
+        .filter(|line| !line.is_empty())
+        .collect()))
+}
+
+fn gpg_id_content(recipients: &[String]) -> String {
+    format!("{}\n", recipients.join("\n"))
+}
+
+/// The fingerprints of the keys `.gpg-id` files have to be signed with, like `pass` reads them.
+fn signing_keys() -> Vec<String> {
+    env::var("PASSWORD_STORE_SIGNING_KEY")
+        .map(|keys| keys.split_whitespace().map(|key| key.to_owned()).collect())
+        .unwrap_or_default()
+}
+
+fn invalid_signature(gpg_id: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("Signature for {} is invalid.", gpg_id.display()),
+    )
+}
+
+/// Check the `.gpg-id.sig` of the `.gpg-id` in `dir`, if there are signing `keys`. The signature
+/// has to be made by one of them or one of their subkeys.
+fn verify_gpg_id(crypto: &dyn CryptoBackend, dir: &Path, keys: &[String]) -> io::Result<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let gpg_id = dir.join(".gpg-id");
+    let signer = crypto
+        .verify(&fs::read(&gpg_id)?, &dir.join(".gpg-id.sig"))
+        .map_err(|_| invalid_signature(&gpg_id))?;
+
+    if keys.iter().any(|key| signer.matches(key)) {
+        Ok(())
+    } else {
+        Err(invalid_signature(&gpg_id))
+    }
+}
+
+/// Sign the `content` of a new `.gpg-id` with one of the signing `keys`, or return `None` if there
+/// are none.
+fn sign_gpg_id(crypto: &dyn CryptoBackend, content: &[u8], keys: &[String]) -> io::Result<Option<Vec<u8>>> {
+    if keys.is_empty() {
+        return Ok(None);
+    }
+
+    crypto
+        .sign(content, keys)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("Could not sign .gpg-id: {}", err)))
+}
+
+/// The commit message `pass` uses when adding a `.gpg-id.sig` made by `signer`.
+fn signing_commit_message(signer: &Signer) -> String {
+    format!("Signing new GPG id with {}.", signer.primary_fingerprint)
+}
+
+fn copy_dir_recursive(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// An entry re-encrypted for the recipients at the destination of a move or copy.
+struct ReencryptedEntry {
+    old_file: PathBuf,
+    new_file: PathBuf,
+    /// The ciphertext before the transfer, to restore a moved entry on failure.
+    original: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+/// Move or copy the entry or folder `source` to `destination` and write the re-encrypted
+/// entries. A folder is merged into an existing folder like `pass cp -f` does, anything else
+/// replaces an existing `destination`. Every step already done is undone on failure, so the
+/// store stays as it was.
+fn transfer_files(source: &Path, destination: &Path, copy: bool, reencrypted: &[ReencryptedEntry]) -> io::Result<()> {
+    let file_name = destination.file_name().unwrap_or("..".as_ref()).to_string_lossy();
+    let backup = destination.with_file_name(format!(".{}.transfer-backup", file_name));
+    fs::create_dir_all(destination.parent().unwrap())?;
+    let merges = source.is_dir() && destination.is_dir();
+    let replaces = !merges && destination.exists();
+    if merges {
+        if let Err(err) = copy_dir_recursive(destination, &backup) {
+            let _ = remove_path(&backup);
+            return Err(err);
+        }
+    } else if replaces {
+        fs::rename(destination, &backup)?;
+    }
+
+    let mut moved = false;
+    let mut result = if merges || (copy && source.is_dir()) {
+        copy_dir_recursive(source, destination)
+    } else if copy {
+        fs::copy(source, destination).map(|_| ())
+    } else {
+        let result = fs::rename(source, destination);
+        moved = result.is_ok();
+        result
+    };
+    for entry in reencrypted {
+        result = result.and_then(|_| write_atomically(&entry.new_file, &entry.ciphertext));
+    }
+
+    match result {
+        Ok(()) => {
+            if merges || replaces {
+                let _ = remove_path(&backup);
+            }
+            // everything is in place at the destination now, so a failure to remove the merged
+            // source leaves some entries behind instead of losing any
+            if merges && !copy {
+                remove_path(source)?;
+            }
+            Ok(())
+        },
+        Err(err) => {
+            if moved {
+                let _ = fs::rename(destination, source);
+                for entry in reencrypted {
+                    let _ = write_atomically(&entry.old_file, &entry.original);
+                }
+            } else if merges || copy {
+                let _ = remove_path(destination);
+            }
+            if merges || replaces {
+                let _ = fs::rename(&backup, destination);
+            }
+            Err(err)
+        },
+    }
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// The temporary file an entry is re-encrypted into before it replaces the entry.
+fn reencryption_tmp_path(file_path: &Path) -> PathBuf {
+    let file_name = file_path.file_name().unwrap_or("..".as_ref()).to_string_lossy();
+    file_path.with_file_name(format!(".{}.reencrypt", file_name))
+}
+
+/// The backup of an entry while it is replaced by its re-encrypted temporary file.
+fn reencryption_backup_path(file_path: &Path) -> PathBuf {
+    let file_name = file_path.file_name().unwrap_or("..".as_ref()).to_string_lossy();
+    file_path.with_file_name(format!(".{}.backup", file_name))
+}
+
+/// Best effort to put back the previous `content` of `path`, or to remove it if it did not exist.
+fn restore_file(path: &Path, content: Option<&[u8]>) {
+    let _ = match content {
+        Some(content) => write_atomically(path, content),
+        None => fs::remove_file(path),
+    };
+}
+
+fn is_special_entry(path: &Path) -> bool {
+    match path.file_name().unwrap_or("..".as_ref()).to_string_lossy().as_ref() {
+        ".git" | ".gitattributes" | ".gpg-id" | ".gpg-id.sig" => true,
+        // hidden files are not listed by `pass` and include our temporary files
+        name => name.starts_with('.'),
+    }
+}
+
+impl PasswordListModel {
+    pub fn visible_page(&self) -> String {
+        if self.show_preferences {
+            "preferences".into()
+        } else if self.onboarding.is_some() {
+            "onboarding".into()
+        } else if self.store_settings.is_some() {
+            "store_settings".into()
+        } else if self.editor.is_some() {
+            "edit_password".into()
+        } else if self.transfer.is_some() {
+            "transfer".into()
+        } else if self.reencryption.is_some() {
+            "init_recipients".into()
+        } else if self.entry_history.is_some() {
+            "password_history".into()
+        } else if self.merge.is_some() {
+            "merge_conflict".into()
+        } else if self.qr_view.is_some() {
+            "qr_code".into()
+        } else if self.current_password.is_some() {
+            "show_password".into()
+        } else {
+            self.history.join("/")
+        }
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        // the onboarding can only be left by setting up a password store, unless it was opened
+        // to switch to another one
+        if self.onboarding.is_some() {
+            return self.show_preferences || self.password_store_path.is_dir();
+        }
+
+        self.history.len() > 1 || self.visible_page() != self.history.join("/")
+    }
+
+    pub fn is_active_page(&self, page: &str) -> bool {
+        self.history.join("/") == page && self.visible_page() == page
+    }
+
+    /// Close all pages shown on top of the password list and the shown password.
+    pub fn close_pages(&mut self) {
+        self.show_preferences = false;
+        self.store_settings = None;
+        self.editor = None;
+        self.transfer = None;
+        self.cancel_reencryption();
+        self.reencryption = None;
+        self.entry_history = None;
+        self.merge = None;
+        self.qr_view = None;
+    }
+
+    pub fn set_crypto_backend(&mut self, kind: CryptoBackendKind) {
+        self.config.crypto_backend = kind;
+        self.crypto = kind.create();
+        if let Err(err) = self.config.save() {
+            self.error = Some(err);
+        }
+
+        // the keys a new store can be created for depend on the backend
+        if self.onboarding.is_some() {
+            self.start_onboarding();
+        }
+    }
+
+    /// Open the first-run assistant for setting up a password store.
+    pub fn start_onboarding(&mut self) {
+        let keys = match self.crypto.list_keys(true) {
+            Ok(keys) => {
+                if let Some(Error::Crypto(_)) = self.error {
+                    self.error = None;
+                }
+                keys
+            },
+            Err(err) => {
+                self.error = Some(err.into());
+                vec![]
+            },
+        };
+
+        self.onboarding = Some(Onboarding {
+            choice: OnboardingChoice::Create,
+            path: self.password_store_path.to_string_lossy().into_owned(),
+            key: keys.first().map(|key| key.fingerprint.clone()),
+            keys,
+            use_git: true,
+            url: String::new(),
+            error: None,
+        });
+    }
+
+    /// Set up the password store as chosen in the onboarding, remember it in the configuration
+    /// and show it.
+    pub fn finish_onboarding(&mut self) {
+        let onboarding = match self.onboarding {
+            Some(ref onboarding) => onboarding.clone(),
+            None => return,
+        };
+
+        let path = expand_home(onboarding.path.trim());
+        let result = if path.as_os_str().is_empty() {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "Choose a directory for the password store"))
+        } else {
+            match onboarding.choice {
+                OnboardingChoice::Create => create_store(
+                    self.crypto.as_ref(),
+                    &self.signing_keys,
+                    &path,
+                    onboarding.key.as_deref(),
+                    onboarding.use_git,
+                ),
+                OnboardingChoice::Clone => GitRepository::clone_from(onboarding.url.trim(), &path)
+                    .map(|_| ())
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string())),
+                OnboardingChoice::Existing if path.is_dir() => Ok(()),
+                OnboardingChoice::Existing => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("The directory {} does not exist", path.display()),
+                )),
+            }
+        };
+
+        if let Err(err) = result {
+            if let Some(ref mut onboarding) = self.onboarding {
+                onboarding.error = Some(err.to_string());
+            }
+            return;
+        }
+
+        self.config.store_path = Some(path.clone());
+        self.untrusted_commits = vec![];
+        self.untrusted_until = None;
+        self.password_store_path = path;
+        self.onboarding = None;
+        self.history = vec![".".into()];
+        self.close_pages();
+        self.hide_password();
+        self.reload_passwords();
+
+        if let Err(err) = self.config.save() {
+            self.error = Some(err);
+        }
+    }
+
+    /// Open the onboarding to switch to another password store.
+    pub fn choose_store(&mut self) {
+        self.error = None;
+        self.close_pages();
+        self.start_onboarding();
+        if let Some(ref mut onboarding) = self.onboarding {
+            onboarding.choice = OnboardingChoice::Existing;
+        }
+    }
+
+    pub fn show_store_settings(&mut self) {
+        let git = self.git();
+        self.store_settings = Some(StoreSettings {
+            has_git: git.is_some(),
+            gpg_diff_problems: git.map(|git| git.gpg_diff_problems()).unwrap_or_default(),
+            error: None,
+        });
+    }
+
+    /// Add the `.gpg` diff attribute to `.gitattributes` and configure the textconv driver like
+    /// `pass git init` does, so decrypted diffs show up on the command line as well.
+    pub fn repair_gpg_diff(&mut self) {
+        let result = match self.git() {
+            Some(git) => configure_gpg_diff(&git, &self.password_store_path),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "The password store is not a git repository")),
+        };
+        self.show_store_settings();
+        if let (Some(settings), Err(err)) = (self.store_settings.as_mut(), result) {
+            settings.error = Some(err.to_string());
+        }
+    }
+
+    pub fn password_file_path(&self, password: &str) -> PathBuf {
+        self.password_store_path.join(format!("{}.gpg", password))
+    }
+
+    pub fn show_password(&mut self, password: String) {
+        self.password_visible = false;
+        self.hotp_code = None;
+        self.otp_error = None;
+        match self.decrypt_password(&password) {
+            Ok(content) => {
+                self.current_content = Some(EntryContent::parse(&content));
+                self.password_error = None;
+            },
+            Err(err) => {
+                self.current_content = None;
+                self.password_error = Some(err.to_string());
+            },
+        }
+        self.current_password = Some(password);
+    }
+
+    /// Copy the password of `password` to the clipboard. Returns whether the clipboard countdown
+    /// has to be started, as it is already running if another secret is still on the clipboard.
+    pub fn copy_password(&mut self, password: String) -> bool {
+        let content = match self.current_content {
+            Some(ref content) if self.current_password.as_ref() == Some(&password) => Ok(content.clone()),
+            _ => self.decrypt_password(&password).map(|content| EntryContent::parse(&content)),
+        };
+        let secret = match content {
+            Ok(content) => content.password,
+            Err(err) => {
+                self.error = Some(err);
+                return false;
+            },
+        };
+
+        self.copy_secret(password, secret)
+    }
+
+    /// The one-time password generators of the shown entry.
+    pub fn otps(&self) -> Vec<Result<Otp, OtpError>> {
+        self.current_content
+            .as_ref()
+            .map(|content| content.otp_uris().into_iter().map(Otp::parse).collect())
+            .unwrap_or_default()
+    }
+
+    fn shows_totp(&self) -> bool {
+        self.otps()
+            .iter()
+            .any(|otp| matches!(otp, Ok(Otp { kind: OtpKind::Totp { .. }, .. })))
+    }
+
+    /// Returns whether the `otp-tick` action has to be scheduled, because TOTP codes are shown
+    /// and no tick is pending yet.
+    pub fn start_otp_countdown(&mut self) -> bool {
+        if self.otp_countdown || !self.shows_totp() {
+            return false;
+        }
+
+        self.otp_countdown = true;
+        true
+    }
+
+    /// Returns whether the TOTP codes need to be updated again.
+    pub fn otp_tick(&mut self) -> bool {
+        self.otp_countdown = self.shows_totp();
+        self.otp_countdown
+    }
+
+    /// Copy the current code of the `index`th one-time password generator of the shown entry.
+    /// Returns whether the clipboard countdown has to be started.
+    pub fn copy_otp(&mut self, index: usize) -> bool {
+        let (password, otp) = match (self.current_password.clone(), self.otps().into_iter().nth(index)) {
+            (Some(password), Some(Ok(otp))) => (password, otp),
+            _ => return false,
+        };
+
+        let code = match (otp.totp(SystemTime::now()), &self.hotp_code) {
+            (Some((code, _)), _) => code,
+            // counter based codes have to be generated explicitly, as this changes the entry
+            (None, Some((hotp_index, code))) if *hotp_index == index => code.clone(),
+            (None, _) => return false,
+        };
+
+        self.copy_secret(password, code)
+    }
+
+    /// Increment the counter of the `index`th HOTP generator of the shown entry and reveal the
+    /// code for the new counter. The code is only revealed once the updated entry is written and
+    /// committed, so the counter never goes out of sync.
+    pub fn generate_hotp(&mut self, index: usize) {
+        self.hotp_code = None;
+        match self.increment_hotp_counter(index) {
+            Ok(code) => {
+                self.hotp_code = Some((index, code));
+                self.otp_error = None;
+            },
+            Err(err) => self.otp_error = Some(err.to_string()),
+        }
+    }
+
+    fn increment_hotp_counter(&mut self, index: usize) -> io::Result<String> {
+        let (password, mut content) = match (&self.current_password, &self.current_content) {
+            (Some(password), Some(content)) => (password.clone(), content.clone()),
+            _ => return Err(io::Error::new(io::ErrorKind::NotFound, "No entry is shown")),
+        };
+        let uri = content.otp_uris()
+            .get(index)
+            .map(|uri| uri.to_string())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "The one-time password does not exist"))?;
+        let otp = Otp::parse(&uri).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        let counter = match otp.kind {
+            OtpKind::Hotp { counter } => counter + 1,
+            OtpKind::Totp { .. } => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Not a counter based one-time password"));
+            },
+        };
+
+        content.replace_otp_uri(index, otp::with_counter(&uri, counter));
+        let file_path = self.password_file_path(&password);
+        let original = fs::read(&file_path)?;
+        self.update_entry(&password, &content.to_string())?;
+        let committed = self.git_commit(
+            &[&file_path],
+            &format!("Increment HOTP counter for {}.", password.trim_start_matches("./")),
+        );
+        if let Err(err) = committed {
+            // the next attempt has to use this counter again, as its code was never shown
+            restore_file(&file_path, Some(&original));
+            self.git_unstage(&[&file_path]);
+            return Err(err.into());
+        }
+        self.current_content = Some(content);
+
+        Ok(otp.code(counter))
+    }
+
+    /// Add the one-time password of the QR code in the image at `image` to the shown entry. The
+    /// image is decoded locally. Google Authenticator exports with several accounts are split
+    /// into one new entry per account next to the shown entry.
+    pub fn add_otp_from_image(&mut self, image: &Path) {
+        match self.import_otp_image(image) {
+            Ok(true) => self.otp_error = None,
+            Ok(false) => self.hide_password(),
+            Err(err) => self.otp_error = Some(err.to_string()),
+        }
+    }
+
+    /// Returns whether the URI was added to the shown entry, instead of split into new entries.
+    fn import_otp_image(&mut self, image: &Path) -> io::Result<bool> {
+        let invalid_data = |err: &dyn fmt::Display| io::Error::new(io::ErrorKind::InvalidData, err.to_string());
+
+        let (password, mut content) = match (&self.current_password, &self.current_content) {
+            (Some(password), Some(content)) => (password.clone(), content.clone()),
+            _ => return Err(io::Error::new(io::ErrorKind::NotFound, "No entry is shown")),
+        };
+
+        let mut uris = vec![];
+        for code in qr::decode_image(image).map_err(|err| invalid_data(&err))? {
+            if code.trim_start().starts_with("otpauth-migration://") {
+                uris.extend(otp::parse_migration(&code).map_err(|err| invalid_data(&err))?);
+            } else {
+                uris.push(code.trim().to_owned());
+            }
+        }
+        let otps = uris
+            .iter()
+            .map(|uri| Otp::parse(uri))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| invalid_data(&err))?;
+
+        if let [uri] = uris.as_slice() {
+            content.lines.push(EntryLine::OtpUri(uri.clone()));
+            self.update_entry(&password, &content.to_string())?;
+            self.current_content = Some(content);
+            self.git_commit(
+                &[&self.password_file_path(&password)],
+                &format!("Add OTP secret for {} to store.", password.trim_start_matches("./")),
+            )?;
+            return Ok(true);
+        }
+
+        let folder = match password.trim_start_matches("./").rfind('/') {
+            Some(index) => format!("{}/", &password.trim_start_matches("./")[..index]),
+            None => String::new(),
+        };
+        self.add_otp_entries(&folder, &uris, otps)?;
+
+        Ok(false)
+    }
+
+    /// Add one new entry in `folder` for each of the one-time passwords. Every account is
+    /// encrypted before any is written, so either all accounts are imported or none.
+    fn add_otp_entries(&mut self, folder: &str, uris: &[String], otps: Vec<Otp>) -> io::Result<()> {
+        let mut entries: Vec<(String, Vec<u8>)> = vec![];
+        for (uri, otp) in uris.iter().zip(otps) {
+            let name = match otp.name().replace('/', "-").trim().trim_start_matches('.') {
+                "" => "OTP".to_owned(),
+                name => name.to_owned(),
+            };
+            let mut password = validate_entry_path(&format!("{}{}", folder, name))?;
+            let mut suffix = 1;
+            while self.password_file_path(&password).exists() || entries.iter().any(|(other, _)| *other == password) {
+                suffix += 1;
+                password = validate_entry_path(&format!("{}{} {}", folder, name, suffix))?;
+            }
+
+            let ciphertext = self.encrypt_for(&self.password_file_path(&password), &format!("{}\n", uri))?;
+            entries.push((password, ciphertext));
+        }
+
+        let file_paths = entries
+            .iter()
+            .map(|(password, _)| self.password_file_path(password))
+            .collect::<Vec<_>>();
+        let mut written = 0;
+        let mut result = Ok(());
+        for (file_path, (_, ciphertext)) in file_paths.iter().zip(&entries) {
+            result = write_atomically(file_path, ciphertext);
+            if result.is_err() {
+                break;
+            }
+            written += 1;
+        }
+        if result.is_ok() {
+            let names = entries
+                .iter()
+                .map(|(password, _)| password.trim_start_matches("./"))
+                .collect::<Vec<_>>();
+            result = self
+                .git_commit(
+                    &file_paths.iter().map(|path| path.as_path()).collect::<Vec<_>>(),
+                    &format!("Add OTP secrets for {} to store.", names.join(", ")),
+                )
+                .map_err(io::Error::from);
+        }
+        if let Err(err) = result {
+            for file_path in &file_paths[..written] {
+                restore_file(file_path, None);
+            }
+            self.git_unstage(&file_paths[..written].iter().map(|path| path.as_path()).collect::<Vec<_>>());
+            return Err(err);
+        }
+
+        for (password, _) in &entries {
+            self.insert_password_node(password);
+        }
+
+        Ok(())
+    }
+
+    /// Show the password of the shown entry as a QR code.
+    pub fn show_qr_code(&mut self) {
+        self.qr_view = Some(QrView {
+            content: QrContent::Password,
+            data: None,
+            error: None,
+        });
+        self.set_qr_content(QrContent::Password);
+    }
+
+    pub fn set_qr_content(&mut self, content: QrContent) {
+        let data = self.qr_data(content);
+        if let Some(ref mut qr_view) = self.qr_view {
+            qr_view.content = content;
+            match data {
+                Ok(data) => {
+                    qr_view.data = Some(data);
+                    qr_view.error = None;
+                },
+                Err(err) => {
+                    qr_view.data = None;
+                    qr_view.error = Some(err);
+                },
+            }
+        }
+    }
+
+    fn qr_data(&self, qr_content: QrContent) -> Result<String, String> {
+        let content = self.current_content.as_ref().ok_or_else(|| "No entry is shown".to_owned())?;
+        let data = match qr_content {
+            QrContent::Password if content.password.is_empty() => return Err("The entry has no password".into()),
+            QrContent::Password => content.password.clone(),
+            QrContent::Otp(index) => content.otp_uris()
+                .get(index)
+                .map(|uri| uri.to_string())
+                .ok_or_else(|| "The one-time password does not exist".to_owned())?,
+            QrContent::Wifi => content.wifi().ok_or_else(|| "The entry has no ssid field".to_owned())?,
+        };
+
+        // fail early if the data does not fit into a QR code
+        qr::encode(&data).map_err(|err| err.to_string())?;
+
+        Ok(data)
+    }
+
+    /// Save the shown QR code as an SVG or PNG image, depending on the extension of `path`.
+    pub fn save_qr_code(&mut self, mut path: PathBuf) {
+        let qr_view = match self.qr_view {
+            Some(ref mut qr_view) => qr_view,
+            None => return,
+        };
+        let data = match qr_view.data {
+            Some(ref data) => data,
+            None => return,
+        };
+
+        if path.extension().is_none() {
+            path.set_extension("png");
+        }
+        qr_view.error = qr::save(data, &path).err().map(|err| err.to_string());
+    }
+
+    /// Put `secret` on the clipboard until `PASSWORD_STORE_CLIP_TIME` elapsed.
+    fn copy_secret(&mut self, password: String, secret: String) -> bool {
+        let start_countdown = !self.clipboard_countdown;
+        self.clipboard_countdown = true;
+        let (previous, previous_is_secret) = match self.clipboard.take() {
+            Some(clipboard) => (clipboard.previous, clipboard.previous_is_secret),
+            None => (clipboard::text(), clipboard::is_secret()),
+        };
+
+        clipboard::set_secret(&secret);
+        self.clipboard = Some(ClipboardSecret {
+            password,
+            secret,
+            previous,
+            previous_is_secret,
+            copied_at: Instant::now(),
+            duration: Duration::from_secs(clipboard::clip_time()),
+        });
+
+        start_countdown
+    }
+
+    /// Clear the copied secret once its time is up. Returns whether the countdown goes on.
+    pub fn clipboard_tick(&mut self) -> bool {
+        self.clipboard_countdown = match self.clipboard {
+            Some(ref clipboard) if clipboard.remaining() > Duration::from_secs(0) => true,
+            Some(_) => {
+                self.restore_clipboard();
+                false
+            },
+            None => false,
+        };
+
+        self.clipboard_countdown
+    }
+
+    /// Remove the copied secret from the clipboard and restore the previous content, unless
+    /// something else was copied in the meantime.
+    pub fn restore_clipboard(&mut self) {
+        let copied = match self.clipboard.take() {
+            Some(copied) => copied,
+            None => return,
+        };
+
+        if clipboard::text().as_ref() == Some(&copied.secret) {
+            match copied.previous {
+                Some(ref previous) if copied.previous_is_secret => clipboard::set_secret(previous),
+                Some(ref previous) => clipboard::set_text(previous),
+                None => clipboard::clear(),
+            }
+        }
+    }
+
+    pub fn hide_password(&mut self) {
+        self.qr_view = None;
+        self.hotp_code = None;
+        self.otp_error = None;
+        self.current_password = None;
+        self.current_content = None;
+        self.password_visible = false;
+        self.password_error = None;
+    }
+
+    /// Open the editor for a new entry in the currently shown directory.
+    pub fn new_entry(&mut self) {
+        let mut path = self.history[1..].join("/");
+        if !path.is_empty() {
+            path.push('/');
+        }
+
+        self.show_preferences = false;
+        self.hide_password();
+        self.editor = Some(Editor::new(path, ""));
+    }
+
+    /// Open the editor for the currently shown entry.
+    pub fn edit_entry(&mut self) {
+        let (password, content) = match (&self.current_password, &self.current_content) {
+            (Some(password), Some(content)) => (password.clone(), content.to_string()),
+            _ => return,
+        };
+
+        self.editor = Some(Editor::edit(password.trim_start_matches("./").to_owned(), &content));
+    }
+
+    /// Save the editor content. Changes to existing entries are reviewed as a diff first.
+    pub fn save_editor(&mut self) {
+        let editor = match self.editor {
+            Some(ref mut editor) => {
+                if editor.original.is_some() && !editor.review {
+                    if editor.original.as_ref() != Some(&editor.text()) {
+                        editor.review = true;
+                        editor.error = None;
+                        return;
+                    }
+                }
+                editor.clone()
+            },
+            None => return,
+        };
+
+        let result = if let Some(ref original) = editor.original {
+            let password = format!("./{}", editor.path);
+            if *original == editor.text() {
+                Ok(password)
+            } else {
+                self.update_entry(&password, &editor.text())
+                    .and_then(|_| {
+                        self.git_commit(
+                            &[&self.password_file_path(&password)],
+                            &format!("Edit password for {} using pass-gtk.", editor.path),
+                        )?;
+                        Ok(password)
+                    })
+            }
+        } else {
+            self.create_entry(&editor.path, &editor.text(), editor.force)
+                .and_then(|password| {
+                    self.git_commit(
+                        &[&self.password_file_path(&password)],
+                        &format!("Add given password for {} to store.", password.trim_start_matches("./")),
+                    )?;
+                    Ok(password)
+                })
+        };
+
+        match result {
+            Ok(password) => {
+                self.editor = None;
+                self.show_password(password);
+            },
+            Err(err) => {
+                if let Some(ref mut editor) = self.editor {
+                    editor.review = false;
+                    editor.error = Some(err.to_string());
+                }
+            },
+        }
+    }
+
+    /// Encrypt `content` to the recipients of the nearest `.gpg-id` and write it as a new entry.
+    /// Returns the password path of the new entry as used by `current_password`.
+    fn create_entry(&mut self, path: &str, content: &str, force: bool) -> io::Result<String> {
+        let password = validate_entry_path(path)?;
+        let file_path = self.password_file_path(&password);
+        if file_path.exists() && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("An entry named '{}' already exists", password.trim_start_matches("./")),
+            ));
+        }
+
+        let ciphertext = self.encrypt_for(&file_path, content)?;
+        fs::create_dir_all(file_path.parent().unwrap())?;
+        write_atomically(&file_path, &ciphertext)?;
+        self.insert_password_node(&password);
+
+        Ok(password)
+    }
+
+    /// Generate a new password for the existing entry at `path` like `pass generate --in-place`
+    /// does, keeping everything but the first line. New entries get their generated password in
+    /// the editor instead.
+    pub fn generate_password(&mut self, path: &str, options: &GeneratorOptions) -> io::Result<String> {
+        let generated = generator::generate(options)?;
+
+        let password = validate_entry_path(path)?;
+        let mut content = EntryContent::parse(&self.decrypt_password(&password)?);
+        content.password = generated;
+        self.update_entry(&password, &content.to_string())?;
+
+        self.git_commit(
+            &[&self.password_file_path(&password)],
+            &format!("Replace generated password for {}.", password.trim_start_matches("./")),
+        )?;
+
+        Ok(password)
+    }
+
+    /// Replace the password of the currently shown entry with a generated one, keeping all
+    /// other lines of the entry.
+    pub fn rotate_password(&mut self) {
+        let password = match self.current_password {
+            Some(ref password) => password.clone(),
+            None => return,
+        };
+        let options = self.generator.clone();
+
+        match self.generate_password(&password, &options) {
+            Ok(password) => {
+                self.show_password(password);
+                self.password_visible = true;
+            },
+            Err(err) => self.password_error = Some(err.to_string()),
+        }
+    }
+
+    /// Replace the password line in the editor with a generated password.
+    pub fn generate_in_editor(&mut self) {
+        let generated = generator::generate(&self.generator);
+        if let Some(ref mut editor) = self.editor {
+            match generated {
+                Ok(password) => {
+                    editor.set_password(password);
+                    editor.error = None;
+                },
+                Err(err) => editor.error = Some(err.to_string()),
+            }
+        }
+    }
+
+    /// Re-encrypt `content` to the current recipients and replace the existing entry with it.
+    fn update_entry(&mut self, password: &str, content: &str) -> io::Result<()> {
+        let file_path = self.password_file_path(password);
+        let ciphertext = self.encrypt_for(&file_path, content)?;
+        write_atomically(&file_path, &ciphertext)
+    }
+
+    /// Encrypt `content` to the recipients responsible for the password file at `file_path`.
+    fn encrypt_for(&self, file_path: &Path, content: &str) -> io::Result<Vec<u8>> {
+        let recipients = self.recipients_for(file_path.parent().unwrap())?;
+        self.crypto
+            .encrypt(content.as_bytes(), &recipients)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+    }
+
+    pub fn git(&self) -> Option<GitRepository> {
+        GitRepository::open(&self.password_store_path)
+    }
+
+    /// Commit the changes to `paths` with a `pass` compatible `message`, if the password store is
+    /// a git repository.
+    fn git_commit(&self, paths: &[&Path], message: &str) -> Result<(), Error> {
+        match self.git() {
+            Some(git) => Ok(git.commit(paths, message)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Bring the index back in line with `paths` after a failed commit, so the undone changes are
+    /// not part of the next commit.
+    fn git_unstage(&self, paths: &[&Path]) {
+        match self.git() {
+            // `git add --all` without paths would stage the whole store
+            Some(git) if !paths.is_empty() => {
+                let _ = git.stage(paths);
+            },
+            _ => {},
+        }
+    }
+
+    pub fn is_directory_path(&self, path: &str) -> bool {
+        self.password_store_path.join(path).is_dir()
+    }
+
+    /// The entries that are removed when deleting the password or directory at `path`, relative
+    /// to the password store.
+    pub fn entries_to_delete(&self, path: &str) -> Vec<String> {
+        let node = match self.find_node(path) {
+            Some(node) => node,
+            None => return vec![],
+        };
+
+        let mut entries = vec![];
+        self.collect_passwords(&node, path, &mut entries);
+        entries.iter().map(|entry| entry.trim_start_matches("./").to_owned()).collect()
+    }
+
+    fn collect_passwords(&self, node: &NodeId, path: &str, passwords: &mut Vec<String>) {
+        match self.passwords.get(node).unwrap().data() {
+            Entry::Password(_) => passwords.push(path.to_owned()),
+            Entry::Directory(_, _) => {
+                for child in self.passwords.children_ids(node).unwrap() {
+                    let name = self.passwords.get(child).unwrap().data().name();
+                    self.collect_passwords(child, &format!("{}/{}", path, name), passwords);
+                }
+            },
+        }
+    }
+
+    /// Delete the password or directory at `path` like `pass rm -r` does, update the tree in place
+    /// and commit the removal if the store is a git repository.
+    pub fn delete_entry(&mut self, path: &str) -> Result<(), Error> {
+        let is_directory = self.is_directory_path(path);
+        let file_path = if is_directory {
+            self.password_store_path.join(path)
+        } else {
+            self.password_file_path(path)
+        };
+
+        let removed = if is_directory {
+            fs::remove_dir_all(&file_path)
+        } else {
+            fs::remove_file(&file_path)
+        };
+        removed.map_err(|err| Error::io(&file_path, err))?;
+
+        if let Some(node) = self.find_node(path) {
+            self.passwords.remove_node(node, RemoveBehavior::DropChildren).unwrap();
+        }
+        self.remove_empty_parents(path);
+        self.fix_navigation();
+
+        self.git_commit(&[&file_path], &format!("Remove {} from store.", path.trim_start_matches("./")))
+    }
+
+    /// Remove the now empty directories above `path`, like `pass rm` does with `rmdir -p`.
+    fn remove_empty_parents(&mut self, path: &str) {
+        let mut components = path.split('/').collect::<Vec<_>>();
+        components.pop();
+
+        while components.len() > 1 {
+            let dir = components.join("/");
+            if fs::remove_dir(self.password_store_path.join(&dir)).is_err() {
+                break;
+            }
+            if let Some(node) = self.find_node(&dir) {
+                self.passwords.remove_node(node, RemoveBehavior::DropChildren).unwrap();
+            }
+            components.pop();
+        }
+    }
+
+    /// Make sure `history` and `current_password` only point to entries that still exist.
+    pub fn fix_navigation(&mut self) {
+        while self.history.len() > 1 && self.find_node(&self.history.join("/")).is_none() {
+            self.history.pop();
+        }
+
+        let password_exists = self.current_password
+            .as_ref()
+            .map(|password| self.find_node(password).is_some())
+            .unwrap_or(true);
+        if !password_exists {
+            self.hide_password();
+        }
+    }
+
+    /// Open the move/copy page for the password or directory at `path`.
+    pub fn start_transfer(&mut self, path: String) {
+        let destination = path.trim_start_matches("./").to_owned();
+        self.transfer = Some(Transfer {
+            source: path,
+            destination,
+            force: false,
+            error: None,
+        });
+    }
+
+    pub fn finish_transfer(&mut self, copy: bool) {
+        let transfer = match self.transfer {
+            Some(ref transfer) => transfer.clone(),
+            None => return,
+        };
+
+        match self.transfer_entry(&transfer.source, &transfer.destination, copy, transfer.force) {
+            Ok(destination) => {
+                self.transfer = None;
+                if !copy && self.current_password.as_ref() == Some(&transfer.source) {
+                    self.show_password(destination);
+                }
+            },
+            Err(err) => {
+                if let Some(ref mut transfer) = self.transfer {
+                    transfer.error = Some(err.to_string());
+                }
+            },
+        }
+    }
+
+    /// Move or copy the password or directory at `source` to `destination` with the semantics of
+    /// `pass mv` and `pass cp`. A `destination` ending in `/` or naming an existing directory
+    /// receives the source under its own name.
+    ///
+    /// Entries whose effective `.gpg-id` recipients differ between source and destination are
+    /// re-encrypted, so they stay readable for the people of the destination folder. Returns the
+    /// path of the moved or copied entry.
+    pub fn transfer_entry(&mut self, source: &str, destination: &str, copy: bool, force: bool) -> io::Result<String> {
+        let source = validate_entry_path(source)?;
+        let source_name = source.split('/').last().unwrap().to_owned();
+        let mut destination_path = validate_entry_path(destination)?;
+        if destination.trim().ends_with('/') || self.is_directory_path(&destination_path) {
+            destination_path = format!("{}/{}", destination_path, source_name);
+        }
+
+        if destination_path == source || destination_path.starts_with(&format!("{}/", source)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot move an entry into itself"));
+        }
+
+        let is_directory = self.is_directory_path(&source);
+        let (source_file, destination_file) = if is_directory {
+            (self.password_store_path.join(&source), self.password_store_path.join(&destination_path))
+        } else {
+            (self.password_file_path(&source), self.password_file_path(&destination_path))
+        };
+
+        if !source_file.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("'{}' does not exist", source.trim_start_matches("./"))));
+        }
+        if destination_file.exists() && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("'{}' already exists", destination_path.trim_start_matches("./")),
+            ));
+        }
+
+        // re-encrypt every entry whose recipients change before touching any file, so a missing
+        // key or a failing decryption leaves the store as it was
+        let mut passwords = vec![];
+        if let Some(node) = self.find_node(&source) {
+            self.collect_passwords(&node, &source, &mut passwords);
+        }
+        let mut transferred = vec![];
+        let mut reencrypted = vec![];
+        for password in passwords {
+            let new_password = format!("{}{}", destination_path, &password[source.len()..]);
+            let old_file = self.password_file_path(&password);
+            let new_file = self.password_file_path(&new_password);
+            let mut old_recipients = self.recipients_for(old_file.parent().unwrap())?;
+            let mut new_recipients =
+                self.transferred_recipients(new_file.parent().unwrap(), &source_file, &destination_file)?;
+            old_recipients.sort();
+            new_recipients.sort();
+
+            if new_recipients != old_recipients {
+                let content = self.decrypt_password(&password)?;
+                let ciphertext = self.crypto
+                    .encrypt(content.as_bytes(), &new_recipients)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+                reencrypted.push(ReencryptedEntry {
+                    original: fs::read(&old_file)?,
+                    old_file,
+                    new_file,
+                    ciphertext,
+                });
+            }
+            transferred.push((password, new_password));
+        }
+
+        if let Err(err) = transfer_files(&source_file, &destination_file, copy, &reencrypted) {
+            // the files were restored, but the tree might not match them anymore
+            self.reload_keeping_navigation();
+            return Err(err);
+        }
+
+        for (password, new_password) in transferred {
+            if !copy {
+                if let Some(node) = self.find_node(&password) {
+                    self.passwords.remove_node(node, RemoveBehavior::DropChildren).unwrap();
+                }
+            }
+            self.insert_password_node(&new_password);
+        }
+
+        if !copy {
+            if let Some(node) = self.find_node(&source) {
+                self.passwords.remove_node(node, RemoveBehavior::DropChildren).unwrap();
+            }
+            self.remove_empty_parents(&source);
+            self.fix_navigation();
+        }
+
+        let message = format!(
+            "{} {} to {}.",
+            if copy { "Copy" } else { "Rename" },
+            source.trim_start_matches("./"),
+            destination_path.trim_start_matches("./"),
+        );
+        self.git_commit(&[&source_file, &destination_file], &message)?;
+
+        Ok(destination_path)
+    }
+
+    /// Open the page to change the recipients of the folder at `folder`.
+    pub fn start_reencryption(&mut self, folder: String) {
+        let recipients = self.directory_recipients(&folder);
+        self.reencryption = Some(Reencryption {
+            folder,
+            recipients_text: recipients.keys.join(" "),
+            recipients: vec![],
+            entries: vec![],
+            staged: vec![],
+            failed: vec![],
+            signature: None,
+            state: ReencryptionState::Editing,
+            error: None,
+        });
+    }
+
+    /// Collect the entries to re-encrypt and start the run. Returns whether re-encryption steps
+    /// have to follow.
+    pub fn begin_reencryption(&mut self) -> bool {
+        let (folder, recipients) = match self.reencryption {
+            Some(ref reencryption) if reencryption.state == ReencryptionState::Editing => (
+                reencryption.folder.clone(),
+                reencryption.recipients_text
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|key| !key.is_empty())
+                    .map(|key| key.to_owned())
+                    .collect::<Vec<_>>(),
+            ),
+            _ => return false,
+        };
+
+        if recipients.is_empty() {
+            if let Some(ref mut reencryption) = self.reencryption {
+                reencryption.error = Some("Please enter at least one recipient".into());
+            }
+            return false;
+        }
+
+        // sign before re-encrypting anything, so a missing signing key does not waste a run
+        let signature = match sign_gpg_id(self.crypto.as_ref(), gpg_id_content(&recipients).as_bytes(), &self.signing_keys) {
+            Ok(signature) => signature,
+            Err(err) => {
+                if let Some(ref mut reencryption) = self.reencryption {
+                    reencryption.error = Some(err.to_string());
+                }
+                return false;
+            },
+        };
+
+        // entries in subfolders with their own .gpg-id keep their recipients
+        let subfolder_prefix = format!("{}/", folder);
+        let mut passwords = vec![];
+        if let Some(node) = self.find_node(&folder) {
+            self.collect_passwords(&node, &folder, &mut passwords);
+        }
+        let entries = passwords
+            .into_iter()
+            .filter(|password| {
+                let parent = &password[..password.rfind('/').unwrap()];
+                let source = self.directory_recipients(parent).source;
+                !source.map(|source| source.starts_with(&subfolder_prefix)).unwrap_or(false)
+            })
+            .collect();
+
+        if let Some(ref mut reencryption) = self.reencryption {
+            reencryption.recipients = recipients;
+            reencryption.entries = entries;
+            reencryption.signature = signature;
+            reencryption.state = ReencryptionState::Running;
+            reencryption.error = None;
+        }
+
+        true
+    }
+
+    /// Re-encrypt the next entry into a temporary file. Returns whether more steps have to follow.
+    pub fn reencryption_step(&mut self) -> bool {
+        let (password, recipients) = match self.reencryption {
+            Some(ref reencryption) if reencryption.state == ReencryptionState::Running => {
+                match reencryption.current_entry() {
+                    Some(password) => (password.clone(), reencryption.recipients.clone()),
+                    None => {
+                        self.apply_reencryption();
+                        return false;
+                    },
+                }
+            },
+            _ => return false,
+        };
+
+        let file_path = self.password_file_path(&password);
+        let result = fs::read(&file_path)
+            .and_then(|ciphertext| {
+                self.crypto
+                    .decrypt(&ciphertext)
+                    .and_then(|plaintext| self.crypto.encrypt(&plaintext, &recipients))
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+            })
+            .and_then(|ciphertext| write_atomically(&reencryption_tmp_path(&file_path), &ciphertext));
+
+        if let Some(ref mut reencryption) = self.reencryption {
+            match result {
+                Ok(()) => reencryption.staged.push(password),
+                Err(err) => reencryption.failed.push((password, err.to_string())),
+            }
+        }
+
+        true
+    }
+
+    /// Stop a running re-encryption and remove all temporary files.
+    pub fn cancel_reencryption(&mut self) {
+        let staged = match self.reencryption {
+            Some(ref mut reencryption) if reencryption.state == ReencryptionState::Running => {
+                reencryption.state = ReencryptionState::Aborted;
+                reencryption.staged.clone()
+            },
+            _ => {
+                self.reencryption = None;
+                return;
+            },
+        };
+
+        for password in staged {
+            let _ = fs::remove_file(reencryption_tmp_path(&self.password_file_path(&password)));
+        }
+    }
+
+    /// Put the new `.gpg-id` and all re-encrypted entries in place, if every entry succeeded.
+    fn apply_reencryption(&mut self) {
+        let reencryption = match self.reencryption {
+            Some(ref reencryption) => reencryption.clone(),
+            None => return,
+        };
+
+        let result = if reencryption.failed.is_empty() {
+            self.replace_gpg_id(&reencryption)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{} entries could not be re-encrypted", reencryption.failed.len()),
+            ))
+        };
+
+        if let Err(ref err) = result {
+            for password in &reencryption.staged {
+                let _ = fs::remove_file(reencryption_tmp_path(&self.password_file_path(password)));
+            }
+            if let Some(ref mut reencryption) = self.reencryption {
+                reencryption.state = ReencryptionState::Aborted;
+                reencryption.error = Some(err.to_string());
+            }
+            return;
+        }
+
+        let commit_error = self.commit_reencryption(&reencryption).err();
+        if let Some(ref mut reencryption) = self.reencryption {
+            reencryption.state = ReencryptionState::Finished;
+            reencryption.error = commit_error.map(|err| err.to_string());
+        }
+        self.reload_passwords();
+        self.fix_navigation();
+    }
+
+    /// Put the re-encrypted entries in place and write the new `.gpg-id` last. The replaced
+    /// entries are kept as backups until everything succeeded, so on failure the old entries and
+    /// the old `.gpg-id` are restored and nothing is changed.
+    fn replace_gpg_id(&self, reencryption: &Reencryption) -> io::Result<()> {
+        let folder_path = self.password_store_path.join(&reencryption.folder);
+        let gpg_id = folder_path.join(".gpg-id");
+        let gpg_id_sig = folder_path.join(".gpg-id.sig");
+        let old_gpg_id = fs::read(&gpg_id).ok();
+        let old_signature = fs::read(&gpg_id_sig).ok();
+
+        let mut replaced = vec![];
+        let result = self.replace_reencrypted_entries(reencryption, &mut replaced).and_then(|_| {
+            write_atomically(&gpg_id, gpg_id_content(&reencryption.recipients).as_bytes())?;
+            match reencryption.signature {
+                Some(ref signature) => write_atomically(&gpg_id_sig, signature),
+                None => Ok(()),
+            }
+        });
+
+        if result.is_err() {
+            restore_file(&gpg_id, old_gpg_id.as_deref());
+            restore_file(&gpg_id_sig, old_signature.as_deref());
+            for file_path in replaced.iter().rev() {
+                let _ = fs::rename(reencryption_backup_path(file_path), file_path);
+            }
+        } else {
+            for file_path in &replaced {
+                let _ = fs::remove_file(reencryption_backup_path(file_path));
+            }
+        }
+
+        result
+    }
+
+    /// Replace every staged entry with its re-encrypted temporary file, collecting the replaced
+    /// entries in `replaced`.
+    fn replace_reencrypted_entries(&self, reencryption: &Reencryption, replaced: &mut Vec<PathBuf>) -> io::Result<()> {
+        for password in &reencryption.staged {
+            let file_path = self.password_file_path(password);
+            fs::rename(&file_path, reencryption_backup_path(&file_path))?;
+            replaced.push(file_path.clone());
+            fs::rename(reencryption_tmp_path(&file_path), &file_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Commit a finished re-encryption with the same two commits `pass init` creates.
+    fn commit_reencryption(&self, reencryption: &Reencryption) -> Result<(), Error> {
+        let folder_path = self.password_store_path.join(&reencryption.folder);
+        let gpg_id = folder_path.join(".gpg-id");
+        let recipients = reencryption.recipients.join(", ");
+        let subfolder = reencryption.folder.trim_start_matches('.').trim_start_matches('/');
+        self.git_commit(&[&gpg_id], &format!("Set GPG id to {}.", recipients))?;
+        if reencryption.signature.is_some() {
+            let gpg_id_sig = folder_path.join(".gpg-id.sig");
+            let content = fs::read(&gpg_id).map_err(|err| Error::io(&gpg_id, err))?;
+            let signer = self.crypto.verify(&content, &gpg_id_sig)?;
+            self.git_commit(&[&gpg_id_sig], &signing_commit_message(&signer))?;
+        }
+        self.git_commit(&[&folder_path], &format!(
+            "Reencrypt password store using new GPG id {}{}.",
+            recipients,
+            if subfolder.is_empty() { String::new() } else { format!(" ({})", subfolder) },
+        ))
+    }
+
+    /// Open the history page listing all commits of the currently shown entry.
+    pub fn show_entry_history(&mut self) {
+        let password = match self.current_password {
+            Some(ref password) => password.clone(),
+            None => return,
+        };
+
+        let commits = match self.git() {
+            Some(git) => git.log(&self.password_file_path(&password)).map_err(|err| err.to_string()),
+            None => Err("The password store is not a git repository".to_owned()),
+        };
+
+        self.entry_history = Some(match commits {
+            Ok(commits) => EntryHistory {
+                password,
+                commits,
+                selected: None,
+                selected_content: None,
+                diff: vec![],
+                error: None,
+            },
+            Err(error) => EntryHistory {
+                password,
+                commits: vec![],
+                selected: None,
+                selected_content: None,
+                diff: vec![],
+                error: Some(error),
+            },
+        });
+    }
+
+    /// Decrypt the entry as of the commit `hash` and diff it against the current content.
+    pub fn select_revision(&mut self, hash: String) {
+        let (commit, current) = match self.entry_history {
+            Some(ref history) => match history.commits.iter().find(|commit| commit.hash == hash) {
+                Some(commit) => (commit.clone(), self.current_content.as_ref().map(|content| content.to_string())),
+                None => return,
+            },
+            None => return,
+        };
+
+        let content = self.git()
+            .ok_or_else(|| "The password store is not a git repository".to_owned())
+            .and_then(|git| git.show_file(&commit.hash, &commit.path).map_err(|err| err.to_string()))
+            .and_then(|ciphertext| self.crypto.decrypt(&ciphertext).map_err(|err| err.to_string()))
+            .and_then(|plaintext| String::from_utf8(plaintext).map_err(|err| err.to_string()));
+
+        if let Some(ref mut history) = self.entry_history {
+            history.selected = Some(hash);
+            match content {
+                Ok(content) => {
+                    history.diff = diff::diff_lines(&current.unwrap_or_default(), &content);
+                    history.selected_content = Some(content);
+                    history.error = None;
+                },
+                Err(error) => {
+                    history.diff = vec![];
+                    history.selected_content = None;
+                    history.error = Some(error);
+                },
+            }
+        }
+    }
+
+    /// Replace the current content of the entry with the selected revision.
+    pub fn restore_revision(&mut self) {
+        let (password, hash, content) = match self.entry_history {
+            Some(EntryHistory { ref password, selected: Some(ref hash), selected_content: Some(ref content), .. }) => {
+                (password.clone(), hash.clone(), content.clone())
+            },
+            _ => return,
+        };
+
+        let result = self.update_entry(&password, &content).and_then(|_| {
+            self.git_commit(
+                &[&self.password_file_path(&password)],
+                &format!("Restore password for {} to revision {}.", password.trim_start_matches("./"), &hash[..7.min(hash.len())]),
+            )?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                self.entry_history = None;
+                self.show_password(password);
+            },
+            Err(err) => {
+                if let Some(ref mut history) = self.entry_history {
+                    history.error = Some(err.to_string());
+                }
+            },
+        }
+    }
+
+    /// Reload the password tree while staying on the shown directory and password, as far as
+    /// they still exist.
+    pub fn reload_keeping_navigation(&mut self) {
+        self.reload_passwords();
+        self.fix_navigation();
+        if let Some(password) = self.current_password.clone() {
+            let visible = self.password_visible;
+            self.show_password(password);
+            self.password_visible = visible;
+        }
+    }
+
+    fn refresh_sync_status(&mut self) {
+        let git = match self.git() {
+            Some(git) => git,
+            None => {
+                self.sync = None;
+                self.conflicts = vec![];
+                return;
+            },
+        };
+
+        self.conflicts = git.conflicted_files()
+            .unwrap_or_default()
+            .iter()
+            .filter(|file| file.ends_with(".gpg"))
+            .map(|file| format!("./{}", file.trim_end_matches(".gpg")))
+            .collect();
+
+        let mut sync = self.sync.take().unwrap_or_default();
+        sync.ahead_behind = git.ahead_behind();
+        sync.last_sync = sync.last_sync.or_else(|| git.last_fetch());
+        self.sync = Some(sync);
+    }
+
+    /// Start rebasing onto the remote changes with `git pull --rebase`, pushing with `git push`
+    /// or both in the background. Returns whether the `sync-tick` action has to be scheduled to
+    /// wait for git.
+    pub fn start_sync(&mut self, pull: bool, push: bool) -> bool {
+        if self.is_syncing() || !(pull || push) {
+            return false;
+        }
+        let git = match self.git() {
+            Some(git) => git,
+            None => return false,
+        };
+
+        match if pull { git.pull() } else { git.push() } {
+            Ok(process) => {
+                let mut sync = self.sync.take().unwrap_or_default();
+                sync.job = Some(SyncJob {
+                    pulling: pull,
+                    push_after_pull: pull && push,
+                    upstream: if pull { git.upstream() } else { None },
+                    process: Rc::new(RefCell::new(process)),
+                });
+                self.sync = Some(sync);
+                true
+            },
+            Err(err) => {
+                self.finish_sync(Err(err.to_string()));
+                false
+            },
+        }
+    }
+
+    pub fn is_syncing(&self) -> bool {
+        self.sync.as_ref().map(|sync| sync.job.is_some()).unwrap_or(false)
+    }
+
+    /// Check whether the running pull or push finished. A finished pull reloads the passwords and
+    /// starts the push of a sync. Returns whether git is still running.
+    pub fn sync_tick(&mut self) -> bool {
+        let job = match self.sync.as_ref().and_then(|sync| sync.job.clone()) {
+            Some(job) => job,
+            None => return false,
+        };
+        let result = job.process.borrow_mut().try_finish();
+        let result = match result {
+            Some(result) => result,
+            None => return true,
+        };
 
-use std::{io, fs, env};
-use std::path::{Path, PathBuf};
-use std::cmp::Ordering;
+        if let Some(ref mut sync) = self.sync {
+            sync.job = None;
+        }
+        let succeeded = result.is_ok();
+        self.finish_sync(result.map_err(|err| err.to_string()));
+        if !job.pulling {
+            if succeeded {
+                self.trust_pushed_commits();
+            }
+            return false;
+        }
 
-#[derive(Clone, Debug)]
-pub enum Entry {
-    Password(String),
-    Directory(String),
-}
+        // a rebase stopped by conflicts has fetched as well
+        if let Some(ref upstream) = job.upstream {
+            self.verify_pulled_commits(upstream);
+        }
+        self.reload_keeping_navigation();
+        let conflicts = self.conflicts.len();
+        if let (Some(sync), true) = (self.sync.as_mut(), conflicts > 0) {
+            sync.error = Some(format!("{} entries have merge conflicts and need to be resolved", conflicts));
+        }
 
-#[derive(Clone, Debug)]
-pub struct PasswordListModel {
-    pub passwords: Tree<Entry>,
-    pub password_store_path: PathBuf,
-    pub history: Vec<String>,
-    pub current_password: Option<String>,
-    pub error: Option<String>,
-}
+        succeeded && conflicts == 0 && job.push_after_pull && self.start_sync(false, true)
+    }
 
-impl Default for PasswordListModel {
-    fn default() -> Self {
-        let password_store_path = env::var("PASSWORD_STORE_DIR")
-            .ok()
-            .and_then(|password_store_dir| {
-                let path = Path::new(&password_store_dir);
-                if path.read_dir().is_ok() {
-                    Some(path.to_owned())
-                } else {
-                    None
-                }
+    fn finish_sync(&mut self, result: Result<(), String>) {
+        let mut sync = self.sync.take().unwrap_or_default();
+        match result {
+            Ok(()) => {
+                sync.last_sync = Some(SystemTime::now());
+                sync.error = None;
+            },
+            Err(error) => sync.error = Some(error),
+        }
+        self.sync = Some(sync);
+        self.refresh_sync_status();
+    }
+
+    pub fn has_conflict(&self, password: &str) -> bool {
+        self.conflicts.iter().any(|conflict| conflict == password)
+    }
+
+    /// Decrypt the base, ours and theirs versions of a conflicted entry and open the merge editor
+    /// with all non-conflicting changes already merged.
+    pub fn start_merge(&mut self, password: String) {
+        let git = match self.git() {
+            Some(git) => git,
+            None => return,
+        };
+
+        let file = format!("{}.gpg", password.trim_start_matches("./"));
+        let decrypt_stage = |stage| {
+            git.conflict_stage(stage, &file)
+                .map(|ciphertext| {
+                    self.crypto
+                        .decrypt(&ciphertext)
+                        .map(|plaintext| String::from_utf8_lossy(&plaintext).into_owned())
+                        .map_err(|err| err.to_string())
+                })
+                .transpose()
+        };
+
+        // while rebasing, "ours" is the upstream branch and "theirs" the local commit
+        let (ours_label, theirs_label) = if git.is_rebasing() {
+            ("remote", "local")
+        } else {
+            ("local", "remote")
+        };
+
+        let versions = decrypt_stage(1)
+            .and_then(|base| Ok((base, decrypt_stage(2)?, decrypt_stage(3)?)));
+        let (base, ours, theirs, error) = match versions {
+            Ok((base, ours, theirs)) => (base, ours, theirs, None),
+            Err(error) => (None, None, None, Some(error)),
+        };
+
+        let deleted_by = match (&ours, &theirs) {
+            _ if error.is_some() => None,
+            (None, Some(_)) => Some(ours_label.to_owned()),
+            (Some(_), None) => Some(theirs_label.to_owned()),
+            (None, None) => Some("both sides".to_owned()),
+            (Some(_), Some(_)) => None,
+        };
+        // a deleted entry can only be kept deleted or restored from the changed version
+        let merged = match (&ours, &theirs) {
+            _ if error.is_some() => String::new(),
+            (Some(ours), Some(theirs)) => diff::merge3(
+                base.as_ref().map(|base| base.as_str()).unwrap_or(""),
+                ours,
+                theirs,
+                ours_label,
+                theirs_label,
+            ).0,
+            (Some(changed), None) | (None, Some(changed)) => changed.clone(),
+            (None, None) => base.clone().unwrap_or_default(),
+        };
+        let buffer = TextBuffer::new(None::<&TextTagTable>);
+        buffer.set_text(&merged);
+
+        self.merge = Some(MergeEditor {
+            password,
+            base,
+            ours,
+            theirs,
+            ours_label: ours_label.to_owned(),
+            theirs_label: theirs_label.to_owned(),
+            buffer,
+            deleted_by,
+            error,
+            save_error: None,
+        });
+    }
+
+    /// Encrypt the merge result, mark the conflict as resolved and continue the rebase or merge.
+    /// This restores an entry one side deleted.
+    pub fn save_merge(&mut self) {
+        let (password, content) = match self.merge {
+            // without the decrypted versions, the buffer does not contain the entry
+            Some(ref merge) if merge.error.is_some() => return,
+            Some(ref merge) => (merge.password.clone(), merge.text()),
+            None => return,
+        };
+
+        let has_markers = content.lines().any(|line| {
+            ["<<<<<<<", "|||||||", "=======", ">>>>>>>"].iter().any(|marker| line.starts_with(marker))
+        });
+        let result = if has_markers {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "Please resolve all conflict markers first"))
+        } else {
+            self.update_entry(&password, &content).and_then(|_| {
+                self.merge_repository()?
+                    .resolve(&format!("{}.gpg", password.trim_start_matches("./")))
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
             })
-            .or(BaseDirs::new().map(|base_dirs| base_dirs.home_dir().join(".password-store")))
-            .expect("Environment variable PASSWORD_STORE_DIR not set and/or no home directory found for this user!");
+        };
 
-        let mut me = Self {
-            passwords: Tree::new(),
-            password_store_path,
-            history: vec![".".into()],
-            current_password: None,
-            error: None,
+        self.finish_merge(result);
+    }
+
+    /// Resolve a modify/delete conflict by keeping the entry deleted.
+    pub fn keep_merge_deleted(&mut self) {
+        let password = match self.merge {
+            Some(ref merge) if merge.deleted_by.is_some() => merge.password.clone(),
+            _ => return,
         };
-        me.reload_passwords();
 
-        me
+        let result = self.merge_repository().and_then(|git| {
+            git.resolve_deleted(&format!("{}.gpg", password.trim_start_matches("./")))
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+        });
+        if result.is_ok() && self.current_password.as_ref() == Some(&password) {
+            self.hide_password();
+        }
+
+        self.finish_merge(result);
     }
-}
 
-fn is_special_entry(path: &Path) -> bool {
-    match path.file_name().unwrap_or("..".as_ref()).to_string_lossy().as_ref() {
-        ".git" | ".gitattributes" | ".gpg-id" => true,
-        _ => false,
+    fn merge_repository(&self) -> io::Result<GitRepository> {
+        self.git()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "The password store is not a git repository"))
     }
-}
 
-impl PasswordListModel {
-    pub fn visible_page(&self) -> String {
-        if self.current_password.is_some() {
-            "show_password".into()
-        } else {
-            self.history.join("/")
+    fn finish_merge(&mut self, result: io::Result<()>) {
+        match result {
+            Ok(()) => {
+                self.merge = None;
+                self.reload_keeping_navigation();
+            },
+            Err(err) => {
+                if let Some(ref mut merge) = self.merge {
+                    merge.save_error = Some(err.to_string());
+                }
+            },
         }
     }
 
-    pub fn can_go_back(&self) -> bool {
-        self.history.len() > 1 || self.current_password.is_some()
+    /// The node of the password or directory at `path`, e.g. `./email/example.com`.
+    pub fn find_node(&self, path: &str) -> Option<NodeId> {
+        let mut node = self.passwords.root_node_id()?.clone();
+        for component in path.split('/').skip(1) {
+            node = self.find_child(&node, component)?;
+        }
+
+        Some(node)
     }
 
-    pub fn is_active_page(&self, page: &str) -> bool {
-        self.history.join("/") == page && self.current_password.is_none()
+    /// The recipients of the `.gpg-id` file nearest to `dir`, searching upwards until the root of
+    /// the password store. Fails if the `.gpg-id` lacks a valid signature while
+    /// `PASSWORD_STORE_SIGNING_KEY` is set.
+    pub fn recipients_for(&self, dir: &Path) -> io::Result<Vec<String>> {
+        let mut current = Some(dir);
+        while let Some(dir) = current {
+            if let Some(keys) = read_gpg_id(dir)? {
+                verify_gpg_id(self.crypto.as_ref(), dir, &self.signing_keys)?;
+                return Ok(keys);
+            }
+
+            if dir == self.password_store_path {
+                break;
+            }
+            current = dir.parent();
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No .gpg-id found, please initialize the password store first",
+        ))
+    }
+
+    /// The recipients of the directory `dir` once `source` was moved or copied to `destination`.
+    /// A `.gpg-id` inside of a transferred folder travels along with it and replaces the one of
+    /// a folder it is merged into.
+    fn transferred_recipients(&self, dir: &Path, source: &Path, destination: &Path) -> io::Result<Vec<String>> {
+        let mut current = Some(dir);
+        while let Some(dir) = current {
+            let candidates = match dir.strip_prefix(destination) {
+                Ok(relative) => vec![source.join(relative), dir.to_owned()],
+                Err(_) => vec![dir.to_owned()],
+            };
+            for candidate in candidates {
+                if let Some(keys) = read_gpg_id(&candidate)? {
+                    verify_gpg_id(self.crypto.as_ref(), &candidate, &self.signing_keys)?;
+                    return Ok(keys);
+                }
+            }
+
+            if dir == self.password_store_path {
+                break;
+            }
+            current = dir.parent();
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No .gpg-id found, please initialize the password store first",
+        ))
+    }
+
+    /// The effective recipients of the directory at `path`, e.g. `./team`, as stored in its
+    /// `Entry::Directory` node.
+    pub fn directory_recipients(&self, path: &str) -> Recipients {
+        self.find_node(path)
+            .and_then(|node| match self.passwords.get(&node).unwrap().data() {
+                Entry::Directory(_, recipients) => Some(recipients.clone()),
+                Entry::Password(_) => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Read the effective recipients of the directory at `path` from the file system.
+    fn find_recipients(&self, path: &str) -> Recipients {
+        let mut components = path.split('/').collect::<Vec<_>>();
+        while !components.is_empty() {
+            let dir = components.join("/");
+            if let Ok(Some(keys)) = read_gpg_id(&self.password_store_path.join(&dir)) {
+                return Recipients {
+                    keys,
+                    source: Some(dir),
+                };
+            }
+            components.pop();
+        }
+
+        Recipients::default()
+    }
+
+    /// Insert a node for the password at `password` into the tree, creating directory nodes as
+    /// needed and keeping the same order as `load_passwords_from_dir`.
+    fn insert_password_node(&mut self, password: &str) {
+        let mut parent = self.passwords.root_node_id().unwrap().clone();
+        let mut components = password.split('/').skip(1).collect::<Vec<_>>();
+        let name = components.pop().unwrap_or("").to_owned();
+
+        let mut path = ".".to_owned();
+        for component in components {
+            path = format!("{}/{}", path, component);
+            parent = match self.find_child(&parent, component) {
+                Some(child) => child,
+                None => {
+                    let recipients = self.find_recipients(&path);
+                    let child = self.passwords.insert(
+                        Node::new(Entry::Directory(component.to_owned(), recipients)),
+                        InsertBehavior::UnderNode(&parent),
+                    ).unwrap();
+                    self.passwords.sort_children_by(&parent, |a, b| compare_entries(a.data(), b.data())).unwrap();
+                    child
+                },
+            };
+        }
+
+        if self.find_child(&parent, &name).is_none() {
+            self.passwords.insert(Node::new(Entry::Password(name)), InsertBehavior::UnderNode(&parent)).unwrap();
+            self.passwords.sort_children_by(&parent, |a, b| compare_entries(a.data(), b.data())).unwrap();
+        }
+    }
+
+    fn find_child(&self, parent: &NodeId, name: &str) -> Option<NodeId> {
+        self.passwords
+            .children_ids(parent)
+            .unwrap()
+            .find(|child| self.passwords.get(child).unwrap().data().name() == name)
+            .cloned()
+    }
+
+    fn decrypt_password(&self, password: &str) -> Result<String, Error> {
+        let file_path = self.password_file_path(password);
+        let ciphertext = fs::read(&file_path).map_err(|err| Error::io(&file_path, err))?;
+        let plaintext = self.crypto.decrypt(&ciphertext)?;
+
+        String::from_utf8(plaintext).map_err(|err| Error::Parse {
+            path: file_path,
+            message: err.to_string(),
+        })
     }
 
     pub fn reload_passwords(&mut self) {
+        self.error = None;
         self.passwords = Tree::new();
+        let recipients = self.find_recipients(".");
         let root_id = self.passwords
-            .insert(Node::new(Entry::Directory(".".into())), InsertBehavior::AsRoot)
+            .insert(Node::new(Entry::Directory(".".into(), recipients.clone())), InsertBehavior::AsRoot)
             .unwrap();
 
-        if let Err(err) = self.load_passwords_from_dir(&self.password_store_path.clone(), &root_id) {
-            self.error = Some(err.to_string());
+        if !self.password_store_path.is_dir() {
+            self.sync = None;
+            self.conflicts = vec![];
+            self.untrusted_commits = vec![];
+            if self.onboarding.is_none() {
+                self.start_onboarding();
+            }
+            return;
+        }
+
+        if let Err(err) = self.load_passwords_from_dir(&self.password_store_path.clone(), &root_id, ".", &recipients) {
+            self.error = Some(err);
+        }
+        self.refresh_sync_status();
+        self.establish_trust_baseline();
+    }
+
+    /// Trust the current history of a store that is opened for the first time, so only commits
+    /// pulled afterwards are verified.
+    fn establish_trust_baseline(&mut self) {
+        let git = match self.git() {
+            Some(git) => git,
+            None => {
+                self.untrusted_commits = vec![];
+                return;
+            },
+        };
+        if self.config.trusted_commit(&self.password_store_path).is_some() {
+            return;
+        }
+
+        let baseline = git.upstream()
+            .and_then(|upstream| git.commit_id(&upstream))
+            .or_else(|| git.head());
+        self.set_trusted_commit(baseline);
+    }
+
+    /// Check the signatures of the commits a pull brought into the remote-tracking branch
+    /// `upstream`, starting at the trusted commit of the store. The trusted commit moves forward
+    /// as long as all new commits carry good signatures.
+    fn verify_pulled_commits(&mut self, upstream: &str) {
+        let git = match self.git() {
+            Some(git) => git,
+            None => return,
+        };
+        let until = match git.commit_id(upstream) {
+            Some(until) => until,
+            None => return,
+        };
+        let since = match self.config.trusted_commit(&self.password_store_path) {
+            Some(since) => since.to_owned(),
+            None => {
+                self.set_trusted_commit(Some(until));
+                return;
+            },
+        };
+
+        let commits = match git.commit_signatures(&since, &until) {
+            Ok(commits) => commits,
+            Err(err) => {
+                self.error = Some(err.into());
+                return;
+            },
+        };
+
+        self.untrusted_commits = commits
+            .into_iter()
+            .filter(|commit| !commit.status.is_trusted())
+            .collect();
+        if self.untrusted_commits.is_empty() {
+            self.set_trusted_commit(Some(until));
+        } else {
+            self.untrusted_until = Some(until);
+        }
+    }
+
+    /// Accept the currently untrusted commits, e.g. after reviewing them.
+    pub fn trust_commits(&mut self) {
+        let until = self.untrusted_until.take();
+        self.set_trusted_commit(until);
+        self.untrusted_commits = vec![];
+    }
+
+    /// Our own commits are on the remote after a push. They are trusted as long as no pulled
+    /// commits still wait for review, even if their infobar was dismissed.
+    fn trust_pushed_commits(&mut self) {
+        if self.untrusted_until.is_some() {
+            return;
+        }
+        let pushed = self.git().and_then(|git| git.upstream().and_then(|upstream| git.commit_id(&upstream)));
+        self.set_trusted_commit(pushed);
+    }
+
+    fn set_trusted_commit(&mut self, commit: Option<String>) {
+        let commit = match commit {
+            Some(commit) => commit,
+            None => return,
+        };
+        if self.config.trusted_commit(&self.password_store_path) == Some(commit.as_str()) {
+            return;
+        }
+
+        self.config.set_trusted_commit(&self.password_store_path, commit);
+        if let Err(err) = self.config.save() {
+            self.error = Some(err);
         }
     }
 
-    fn load_passwords_from_dir(&mut self, dir: &Path, parent: &NodeId) -> io::Result<()> {
-        let mut read_dir = fs::read_dir(dir)?
+    fn load_passwords_from_dir(
+        &mut self,
+        dir: &Path,
+        parent: &NodeId,
+        parent_path: &str,
+        parent_recipients: &Recipients,
+    ) -> Result<(), Error> {
+        let mut read_dir = fs::read_dir(dir)
+            .map_err(|err| Error::io(dir, err))?
             .filter(|dir_entry| dir_entry.is_ok())
             .map(|dir_entry| dir_entry.unwrap())
             .collect::<Vec<_>>();
@@ -113,16 +2839,26 @@ impl PasswordListModel {
         for entry in read_dir.drain(..) {
             let path = entry.path();
             if path.is_dir() && !is_special_entry(&path) {
+                let name = path.file_name()
+                    .unwrap_or("..".as_ref())
+                    .to_string_lossy()
+                    .to_string();
+                let subdir_path = format!("{}/{}", parent_path, name);
+
+                // a .gpg-id in a subdirectory overrides the recipients of its parents
+                let recipients = match read_gpg_id(&path).map_err(|err| Error::io(&path.join(".gpg-id"), err))? {
+                    Some(keys) => Recipients {
+                        keys,
+                        source: Some(subdir_path.clone()),
+                    },
+                    None => parent_recipients.clone(),
+                };
+
                 let subdir = self.passwords.insert(
-                    Node::new(Entry::Directory(
-                        path.file_name()
-                            .unwrap_or("..".as_ref())
-                            .to_string_lossy()
-                            .to_string()
-                    )),
+                    Node::new(Entry::Directory(name, recipients.clone())),
                     InsertBehavior::UnderNode(parent),
                 ).unwrap();
-                self.load_passwords_from_dir(&path, &subdir)?;
+                self.load_passwords_from_dir(&path, &subdir, &subdir_path, &recipients)?;
             } else if !is_special_entry(&path) {
                 let _pw_id = self.passwords.insert(
                     Node::new(Entry::Password(
@@ -139,3 +2875,472 @@ impl PasswordListModel {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::MockBackend;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    static DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    const MOCK_KEY: &str = "0000000000000000000000000000000000000000";
+    const TEAM_KEY: &str = "1111111111111111111111111111111111111111";
+    const UNKNOWN_KEY: &str = "2222222222222222222222222222222222222222";
+
+    fn two_key_backend() -> MockBackend {
+        MockBackend::new(
+            [MOCK_KEY, TEAM_KEY]
+                .iter()
+                .map(|fingerprint| Key {
+                    fingerprint: (*fingerprint).to_owned(),
+                    user_ids: vec![],
+                    secret: true,
+                })
+                .collect(),
+        )
+    }
+
+    /// A fresh password store for the mock key that is removed again when dropped.
+    struct TempStore(PathBuf);
+
+    impl TempStore {
+        fn new() -> Self {
+            let path = env::temp_dir().join(format!(
+                "pass-gtk-model-test-{}-{}",
+                process::id(),
+                DIR_COUNTER.fetch_add(1, AtomicOrdering::SeqCst),
+            ));
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join(".gpg-id"), format!("{}\n", MOCK_KEY)).unwrap();
+            TempStore(path)
+        }
+
+        fn model(&self) -> PasswordListModel {
+            PasswordListModel::with_backend(Rc::new(MockBackend::default()), &self.0)
+        }
+
+        fn write_entry(&self, password: &str, content: &str) {
+            let file_path = self.0.join(format!("{}.gpg", password));
+            let ciphertext = MockBackend::default().encrypt(content.as_bytes(), &[MOCK_KEY.into()]).unwrap();
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, ciphertext).unwrap();
+        }
+
+        fn read_entry(&self, password: &str) -> String {
+            let ciphertext = fs::read(self.0.join(format!("{}.gpg", password))).unwrap();
+            String::from_utf8(two_key_backend().decrypt(&ciphertext).unwrap()).unwrap()
+        }
+
+        /// The recipients a mock ciphertext was encrypted for.
+        fn entry_recipients(&self, password: &str) -> String {
+            let ciphertext = fs::read_to_string(self.0.join(format!("{}.gpg", password))).unwrap();
+            ciphertext.lines().nth(1).unwrap().to_owned()
+        }
+
+        /// The content of every file in the store, to check that nothing changed or was left behind.
+        fn files(&self) -> Vec<(PathBuf, Vec<u8>)> {
+            fn collect(dir: &Path, files: &mut Vec<(PathBuf, Vec<u8>)>) {
+                for entry in fs::read_dir(dir).unwrap() {
+                    let path = entry.unwrap().path();
+                    if path.is_dir() {
+                        collect(&path, files);
+                    } else {
+                        files.push((path.clone(), fs::read(&path).unwrap()));
+                    }
+                }
+            }
+
+            let mut files = vec![];
+            collect(&self.0, &mut files);
+            files.sort();
+            files
+        }
+
+        fn set_recipients(&self, folder: &str, recipients: &str) {
+            fs::create_dir_all(self.0.join(folder)).unwrap();
+            fs::write(self.0.join(folder).join(".gpg-id"), format!("{}\n", recipients)).unwrap();
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn model_decrypts_entries() {
+        let store = TempStore::new();
+        store.write_entry("web/example.com", "hunter2\nlogin: jane\n");
+        let mut model = store.model();
+        model.show_password("./web/example.com".into());
+
+        assert_eq!(model.password_error, None);
+        let content = model.current_content.unwrap();
+        assert_eq!(content.password, "hunter2");
+        assert_eq!(content.login(), Some("jane"));
+    }
+
+    #[test]
+    fn model_reports_undecryptable_entries() {
+        let store = TempStore::new();
+        fs::write(store.0.join("broken.gpg"), "not a mock message").unwrap();
+        let mut model = store.model();
+        model.show_password("./broken".into());
+
+        assert!(model.current_content.is_none());
+        assert!(model.password_error.is_some());
+    }
+
+    #[test]
+    fn model_creates_entries_for_the_store_recipients() {
+        let store = TempStore::new();
+        let mut model = store.model();
+
+        let password = model.create_entry("web/example.com", "hunter2\n", false).unwrap();
+        assert_eq!(password, "./web/example.com");
+        assert_eq!(store.read_entry("web/example.com"), "hunter2\n");
+        assert!(model.find_node("./web/example.com").is_some());
+
+        assert!(model.create_entry("web/example.com", "other\n", false).is_err());
+        assert_eq!(store.read_entry("web/example.com"), "hunter2\n");
+        model.create_entry("web/example.com", "other\n", true).unwrap();
+        assert_eq!(store.read_entry("web/example.com"), "other\n");
+    }
+
+    #[test]
+    fn model_updates_entries() {
+        let store = TempStore::new();
+        store.write_entry("example.com", "hunter2\n");
+        let mut model = store.model();
+
+        model.update_entry("./example.com", "correct horse\nlogin: jane\n").unwrap();
+        assert_eq!(store.read_entry("example.com"), "correct horse\nlogin: jane\n");
+    }
+
+    #[test]
+    fn transfer_reencrypts_for_the_destination_recipients() {
+        let store = TempStore::new();
+        store.set_recipients("team", TEAM_KEY);
+        store.write_entry("example.com", "hunter2\n");
+        let mut model = PasswordListModel::with_backend(Rc::new(two_key_backend()), &store.0);
+
+        let destination = model.transfer_entry("./example.com", "team/", false, false).unwrap();
+        assert_eq!(destination, "./team/example.com");
+        assert!(!store.0.join("example.com.gpg").exists());
+        assert_eq!(store.entry_recipients("team/example.com"), TEAM_KEY);
+        assert_eq!(store.read_entry("team/example.com"), "hunter2\n");
+        assert!(model.find_node("./example.com").is_none());
+        assert!(model.find_node("./team/example.com").is_some());
+    }
+
+    #[test]
+    fn failed_transfer_reencryption_leaves_the_store_untouched() {
+        let store = TempStore::new();
+        store.set_recipients("team", UNKNOWN_KEY);
+        store.write_entry("web/a", "a\n");
+        store.write_entry("web/b", "b\n");
+        let original = fs::read(store.0.join("web/a.gpg")).unwrap();
+        let mut model = PasswordListModel::with_backend(Rc::new(two_key_backend()), &store.0);
+
+        assert!(model.transfer_entry("./web", "team/", false, false).is_err());
+        assert_eq!(fs::read(store.0.join("web/a.gpg")).unwrap(), original);
+        assert!(store.0.join("web/b.gpg").exists());
+        assert!(!store.0.join("team/web").exists());
+        assert!(model.find_node("./web/a").is_some());
+    }
+
+    #[test]
+    fn forced_transfer_merges_into_an_existing_folder() {
+        let store = TempStore::new();
+        store.write_entry("web/a", "new a\n");
+        store.write_entry("web/b", "b\n");
+        store.write_entry("archive/web/a", "old a\n");
+        store.write_entry("archive/web/c", "c\n");
+        let mut model = store.model();
+
+        assert!(model.transfer_entry("./web", "archive", true, false).is_err());
+        assert_eq!(store.read_entry("archive/web/a"), "old a\n");
+
+        model.transfer_entry("./web", "archive", true, true).unwrap();
+        assert_eq!(store.read_entry("archive/web/a"), "new a\n");
+        assert_eq!(store.read_entry("archive/web/b"), "b\n");
+        assert_eq!(store.read_entry("archive/web/c"), "c\n");
+        assert_eq!(store.read_entry("web/a"), "new a\n");
+
+        model.transfer_entry("./web", "archive", false, true).unwrap();
+        assert!(!store.0.join("web").exists());
+        assert_eq!(store.read_entry("archive/web/c"), "c\n");
+        assert!(model.find_node("./archive/web/c").is_some());
+    }
+
+    #[test]
+    fn failed_merge_restores_source_and_destination() {
+        let store = TempStore::new();
+        store.write_entry("web/a", "new a\n");
+        store.write_entry("archive/web/a", "old a\n");
+        let source = store.0.join("web");
+        let destination = store.0.join("archive/web");
+        // writing into a folder that does not exist fails after the files were merged
+        let reencrypted = [ReencryptedEntry {
+            old_file: source.join("a.gpg"),
+            new_file: destination.join("missing/a.gpg"),
+            original: fs::read(source.join("a.gpg")).unwrap(),
+            ciphertext: vec![],
+        }];
+
+        assert!(transfer_files(&source, &destination, false, &reencrypted).is_err());
+        assert_eq!(store.read_entry("web/a"), "new a\n");
+        assert_eq!(store.read_entry("archive/web/a"), "old a\n");
+        assert!(!store.0.join("archive/.web.transfer-backup").exists());
+    }
+
+    /// A store with entries for the mock key, one of them in a folder with its own `.gpg-id`.
+    fn reencryption_store() -> (TempStore, PasswordListModel) {
+        let store = TempStore::new();
+        store.set_recipients("own", MOCK_KEY);
+        store.write_entry("a", "a\n");
+        store.write_entry("web/b", "b\n");
+        store.write_entry("own/c", "c\n");
+        let mut model = PasswordListModel::with_backend(Rc::new(two_key_backend()), &store.0);
+        model.start_reencryption(".".into());
+        model.reencryption.as_mut().unwrap().recipients_text = TEAM_KEY.into();
+
+        (store, model)
+    }
+
+    #[test]
+    fn reencryption_replaces_every_entry_and_the_gpg_id() {
+        let (store, mut model) = reencryption_store();
+
+        assert!(model.begin_reencryption());
+        while model.reencryption_step() {}
+
+        let reencryption = model.reencryption.as_ref().unwrap();
+        assert_eq!(reencryption.state, ReencryptionState::Finished);
+        assert_eq!(reencryption.error, None);
+        assert_eq!(fs::read_to_string(store.0.join(".gpg-id")).unwrap(), format!("{}\n", TEAM_KEY));
+        assert_eq!(store.entry_recipients("a"), TEAM_KEY);
+        assert_eq!(store.entry_recipients("web/b"), TEAM_KEY);
+        assert_eq!(store.read_entry("web/b"), "b\n");
+        // the subfolder keeps its own recipients
+        assert_eq!(store.entry_recipients("own/c"), MOCK_KEY);
+        assert_eq!(store.files().len(), 5);
+    }
+
+    #[test]
+    fn failed_reencryption_changes_nothing() {
+        let (store, mut model) = reencryption_store();
+        fs::write(store.0.join("web/broken.gpg"), "not a mock message").unwrap();
+        model.reload_passwords();
+        let files = store.files();
+
+        assert!(model.begin_reencryption());
+        while model.reencryption_step() {}
+
+        let reencryption = model.reencryption.as_ref().unwrap();
+        assert_eq!(reencryption.state, ReencryptionState::Aborted);
+        assert_eq!(reencryption.failed.len(), 1);
+        assert!(reencryption.error.is_some());
+        assert_eq!(store.files(), files);
+    }
+
+    #[test]
+    fn cancelled_reencryption_restores_the_original_state() {
+        let (store, mut model) = reencryption_store();
+        let files = store.files();
+
+        assert!(model.begin_reencryption());
+        assert!(model.reencryption_step());
+        assert_eq!(model.reencryption.as_ref().unwrap().staged.len(), 1);
+        model.cancel_reencryption();
+
+        assert_eq!(model.reencryption.as_ref().unwrap().state, ReencryptionState::Aborted);
+        assert!(!model.reencryption_step());
+        assert_eq!(store.files(), files);
+    }
+
+    #[test]
+    fn gpg_id_needs_a_valid_signature_of_a_signing_key() {
+        let store = TempStore::new();
+        let crypto = two_key_backend();
+        let mut model = PasswordListModel::with_backend(Rc::new(crypto.clone()), &store.0);
+        model.signing_keys = vec![format!("0x{}", MOCK_KEY)];
+        let sign = |key: &str| {
+            let gpg_id = fs::read(store.0.join(".gpg-id")).unwrap();
+            fs::write(store.0.join(".gpg-id.sig"), crypto.sign(&gpg_id, &[key.to_owned()]).unwrap()).unwrap();
+        };
+
+        // missing signature
+        assert!(model.recipients_for(&store.0).is_err());
+
+        sign(MOCK_KEY);
+        assert_eq!(model.recipients_for(&store.0).unwrap(), vec![MOCK_KEY.to_owned()]);
+
+        // tampered .gpg-id
+        fs::write(store.0.join(".gpg-id"), format!("{}\n{}\n", MOCK_KEY, TEAM_KEY)).unwrap();
+        assert!(model.recipients_for(&store.0).is_err());
+        assert!(model.create_entry("example.com", "hunter2\n", false).is_err());
+
+        // signed by a key that is not a signing key
+        sign(TEAM_KEY);
+        assert!(model.recipients_for(&store.0).is_err());
+    }
+
+    #[test]
+    fn model_refuses_entries_without_recipients() {
+        let store = TempStore::new();
+        fs::remove_file(store.0.join(".gpg-id")).unwrap();
+        let mut model = store.model();
+
+        assert!(model.create_entry("example.com", "hunter2\n", false).is_err());
+        assert!(!store.0.join("example.com.gpg").exists());
+    }
+
+    const HOTP_ENTRY: &str = "hunter2\notpauth://hotp/Example?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0\n";
+
+    #[test]
+    fn hotp_codes_advance_the_stored_counter() {
+        let store = TempStore::new();
+        store.write_entry("example.com", HOTP_ENTRY);
+        let mut model = store.model();
+        model.show_password("./example.com".into());
+
+        model.generate_hotp(0);
+        assert_eq!(model.otp_error, None);
+        assert_eq!(model.hotp_code, Some((0, "287082".to_owned())));
+        assert!(store.read_entry("example.com").contains("counter=1"));
+        assert_eq!(model.current_content.unwrap().to_string(), store.read_entry("example.com"));
+    }
+
+    #[test]
+    fn failed_hotp_write_keeps_the_counter() {
+        let store = TempStore::new();
+        store.write_entry("example.com", HOTP_ENTRY);
+        let mut model = store.model();
+        model.show_password("./example.com".into());
+        fs::remove_file(store.0.join(".gpg-id")).unwrap();
+        let files = store.files();
+
+        model.generate_hotp(0);
+        assert!(model.otp_error.is_some());
+        assert_eq!(model.hotp_code, None);
+        assert_eq!(store.files(), files);
+        assert_eq!(model.current_content.unwrap().to_string(), HOTP_ENTRY);
+    }
+
+    #[test]
+    fn failed_hotp_commit_keeps_the_counter() {
+        let store = TempStore::new();
+        store.write_entry("example.com", HOTP_ENTRY);
+        let mut model = store.model();
+        model.show_password("./example.com".into());
+
+        GitRepository::init(&store.0).unwrap();
+        for (key, value) in &[("user.name", "Test"), ("user.email", "test@example.com"), ("commit.gpgsign", "false")] {
+            let status = process::Command::new("git").arg("-C").arg(&store.0).args(&["config", key, value]).status().unwrap();
+            assert!(status.success());
+        }
+        model.git_commit(&[&store.0.join("example.com.gpg")], "Add given password for example.com to store.").unwrap();
+        let hook = store.0.join(".git").join("hooks").join("pre-commit");
+        fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+        model.generate_hotp(0);
+        assert!(model.otp_error.is_some());
+        assert_eq!(model.hotp_code, None);
+        assert_eq!(store.read_entry("example.com"), HOTP_ENTRY);
+        assert_eq!(model.current_content.as_ref().unwrap().to_string(), HOTP_ENTRY);
+        let staged = process::Command::new("git").arg("-C").arg(&store.0).args(&["diff", "--cached", "--stat"]).output().unwrap();
+        assert!(staged.stdout.is_empty());
+
+        // the next code is still the one for the first counter value
+        fs::remove_file(&hook).unwrap();
+        model.generate_hotp(0);
+        assert_eq!(model.hotp_code, Some((0, "287082".to_owned())));
+    }
+
+    fn migration_accounts() -> (Vec<String>, Vec<Otp>) {
+        let uris = vec![
+            "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example".to_owned(),
+            "otpauth://totp/Example:alice?secret=GEZDGNBVGY3TQOJQ&issuer=Example".to_owned(),
+        ];
+        let otps = uris.iter().map(|uri| Otp::parse(uri).unwrap()).collect();
+        (uris, otps)
+    }
+
+    #[test]
+    fn migrated_accounts_get_one_entry_each() {
+        let store = TempStore::new();
+        let mut model = store.model();
+        let (uris, otps) = migration_accounts();
+
+        model.add_otp_entries("web/", &uris, otps).unwrap();
+        assert_eq!(store.read_entry("web/Example (alice)"), format!("{}\n", uris[0]));
+        assert_eq!(store.read_entry("web/Example (alice) 2"), format!("{}\n", uris[1]));
+        assert!(model.find_node("./web/Example (alice) 2").is_some());
+    }
+
+    #[test]
+    fn failed_migration_imports_no_account() {
+        let store = TempStore::new();
+        fs::create_dir_all(store.0.join("web")).unwrap();
+        let mut model = store.model();
+        GitRepository::init(&store.0).unwrap();
+        let hook = store.0.join(".git").join("hooks").join("pre-commit");
+        fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        let (uris, otps) = migration_accounts();
+
+        assert!(model.add_otp_entries("web/", &uris, otps).is_err());
+        assert!(fs::read_dir(store.0.join("web")).unwrap().next().is_none());
+        assert!(model.find_node("./web/Example (alice)").is_none());
+        let staged = process::Command::new("git").arg("-C").arg(&store.0).args(&["diff", "--cached", "--stat"]).output().unwrap();
+        assert!(staged.stdout.is_empty());
+    }
+
+    #[test]
+    fn entry_content_round_trip() {
+        let inputs = [
+            "",
+            "\n",
+            "hunter2",
+            "hunter2\n",
+            "hunter2\n\n",
+            "hunter2\nlogin: jane\nurl:https://example.com\n",
+            "hunter2\r\nlogin:\tjane  \n",
+            "hunter2\nlogin: jane\notpauth://totp/Example:jane?secret=JBSWY3DPEHPK3PXP\n\nsome notes\n with: colons\n",
+            "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP",
+            "\nno password\nkey: value",
+        ];
+
+        for input in inputs.iter() {
+            assert_eq!(EntryContent::parse(input).to_string(), *input);
+        }
+    }
+
+    #[test]
+    fn entry_content_parses_pass_conventions() {
+        let content = EntryContent::parse(
+            "hunter2\nUsername: jane\nurl: https://example.com\npin: 1234\notpauth://totp/Example?secret=JBSWY3DPEHPK3PXP\nhttps://example.org\nnote\n",
+        );
+
+        assert_eq!(content.password, "hunter2");
+        assert_eq!(content.login(), Some("jane"));
+        assert_eq!(content.url(), Some("https://example.com"));
+        assert_eq!(content.other_fields(), vec![("pin", "1234")]);
+        assert_eq!(content.otp_uris(), vec!["otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP"]);
+        assert_eq!(content.notes(), "https://example.org\nnote");
+    }
+
+    #[test]
+    fn entry_content_set_field_keeps_other_lines() {
+        let mut content = EntryContent::parse("hunter2\nlogin:  jane\nnote\n");
+        content.set_field("LOGIN", "john");
+        content.set_field("url", "https://example.com");
+
+        assert_eq!(content.to_string(), "hunter2\nlogin:  john\nnote\nurl: https://example.com\n");
+    }
+}
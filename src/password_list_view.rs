@@ -1,12 +1,18 @@
 use crate::prelude::*;
-use crate::password_list_model::Entry;
+use crate::password_list_model::{Entry, EntryContent, Editor, Recipients, ReencryptionState, OnboardingChoice, QrContent};
+use crate::diff::DiffLine;
+use crate::error::ErrorAction;
+use crate::crypto::CryptoBackendKind;
+use crate::otp::{Otp, OtpError, OtpKind};
 use crate::password_list_component::{PasswordListHeaderbar, PasswordList, PasswordListMessage};
 
 use vgtk::{ext::*, gtk, VNode};
 use vgtk::lib::gtk::*;
+use vgtk::lib::glib;
 use id_tree::NodeId;
 use pango::EllipsizeMode;
 use std::iter;
+use std::time::SystemTime;
 
 impl View for PasswordListHeaderbar {
     fn view(&self) -> VNode<Self> {
@@ -17,6 +23,13 @@ impl View for PasswordListHeaderbar {
             )
             .section(
                 vgtk::menu()
+                    .item("Sync", "app.sync")
+                    .item("Pull", "app.pull")
+                    .item("Push", "app.push")
+            )
+            .section(
+                vgtk::menu()
+                    .item("Store settings", "app.store-settings")
                     .item("Preferences", "app.preferences")
                     .item("About", "app.about")
             )
@@ -42,6 +55,14 @@ impl View for PasswordListHeaderbar {
                     on clicked=|_| PasswordListMessage::Back
                 />
 
+                // Create a new entry in the current directory
+                <Button
+                    HeaderBar::pack_type=PackType::End
+                    tooltip_text="New entry"
+                    image="document-new-symbolic"
+                    on clicked=|_| PasswordListMessage::NewEntry
+                />
+
                 // The path bar containing the navigatable password path
                 <Box
                     HeaderBar::pack_type=PackType::Start
@@ -124,11 +145,116 @@ impl View for PasswordListHeaderbar {
                     popover=Some(main_menu_popover)
                     image="open-menu-symbolic"
                 />
+
+                // The sync button showing how far the store is ahead or behind of its remote
+                { self.render_sync_button() }
+
+                // The countdown until a copied secret is removed from the clipboard
+                { self.render_clipboard_countdown() }
             </HeaderBar>
         }
     }
 }
 
+impl PasswordListHeaderbar {
+    fn render_clipboard_countdown(&self) -> VNode<Self> {
+        let (name, remaining, fraction) = match self.model.clipboard {
+            Some(ref clipboard) => (
+                clipboard.password.trim_start_matches("./").to_owned(),
+                clipboard.remaining().as_secs_f64().ceil() as u64,
+                clipboard.remaining().as_secs_f64() / clipboard.duration.as_secs_f64(),
+            ),
+            None => (String::new(), 0, 0.0),
+        };
+
+        gtk! {
+            <Button
+                HeaderBar::pack_type=PackType::End
+                visible=self.model.clipboard.is_some()
+                relief=ReliefStyle::None
+                tooltip_text=format!("The password of {} is on the clipboard. Click to clear it now.", name)
+                on clicked=|_| PasswordListMessage::ClearClipboard
+            >
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=4
+                >
+                    <Image property_icon_name="edit-paste-symbolic" />
+                    <Box
+                        orientation=Orientation::Vertical
+                        valign=Align::Center
+                        spacing=2
+                    >
+                        <Label label=format!("{}s", remaining) />
+                        <LevelBar
+                            value=fraction
+                            width_request=32
+                        />
+                    </Box>
+                </Box>
+            </Button>
+        }
+    }
+
+    fn render_sync_button(&self) -> VNode<Self> {
+        let sync = match self.model.sync {
+            Some(ref sync) => sync,
+            None => return gtk! { <Box HeaderBar::pack_type=PackType::End visible=false /> },
+        };
+
+        let last_sync = match sync.last_sync {
+            Some(last_sync) => format!("Synced {}", format_elapsed(last_sync)),
+            None => "Never synced".to_owned(),
+        };
+        let mut tooltip = "Pull and push the password store".to_owned();
+        if let Some(ref error) = sync.error {
+            tooltip = format!("{}\n\nThe last sync failed: {}", tooltip, error);
+        }
+
+        let syncing = sync.job.is_some();
+        if syncing {
+            tooltip = "Syncing with the remote…".to_owned();
+        }
+
+        gtk! {
+            <Button
+                HeaderBar::pack_type=PackType::End
+                tooltip_text=tooltip
+                sensitive=!syncing
+                on clicked=|_| PasswordListMessage::Sync
+            >
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=4
+                >
+                    <Spinner
+                        active=syncing
+                        visible=syncing
+                    />
+                    <Image
+                        visible=!syncing
+                        property_icon_name=if sync.error.is_some() {
+                            "dialog-warning-symbolic"
+                        } else {
+                            "emblem-synchronizing-symbolic"
+                        }
+                    />
+                    <Label
+                        label=sync.ahead_behind
+                            .map(|(ahead, behind)| format!("↑{} ↓{}", ahead, behind))
+                            .unwrap_or_default()
+                        visible=sync.ahead_behind.is_some()
+                    />
+                    <Label
+                        label=last_sync
+                        classes=vec!["dim-label".into()]
+                    />
+                </Box>
+            </Button>
+        }
+    }
+}
+
 impl View for PasswordList {
     fn view(&self) -> VNode<Self> {
         gtk! {
@@ -136,48 +262,1530 @@ impl View for PasswordList {
                 hscrollbar_policy=PolicyType::Never
                 vscrollbar_policy=PolicyType::Automatic
             >
+                <Box orientation=Orientation::Vertical>
+                    // errors of the password store
+                    { self.render_error_bar() }
+
+                    // commits received from the remote without a trusted signature
+                    { self.render_untrusted_commits_bar() }
+
+                    // who can read the currently shown folder
+                    { self.render_recipients_panel() }
+
+                    <Frame
+                        border_width=24
+                        shadow_type=ShadowType::EtchedIn
+                    >
+                        <Stack
+                            visible_child_name=self.model.visible_page()
+                            homogeneous=false
+                            transition_type=StackTransitionType::SlideLeftRight
+                        >
+                            // pages of the password list
+                            {
+                                let root = self.model.passwords.root_node_id().unwrap();
+                                let entry = self.model.passwords.get(root).unwrap().data().name();
+                                self.render_password_list(root, entry.to_owned())
+                            }
+
+                            // the page showing the decrypted password
+                            { self.render_password_page() }
+
+                            // the editor for new and existing entries
+                            { self.render_editor_page() }
+
+                            // moving and copying entries
+                            { self.render_transfer_page() }
+
+                            // changing the recipients of a folder
+                            { self.render_reencryption_page() }
+
+                            // the git history of the shown entry
+                            { self.render_history_page() }
+
+                            // resolving merge conflicts of an entry
+                            { self.render_merge_page() }
+
+                            // the shown entry as a QR code
+                            { self.render_qr_code_page() }
+
+                            // setting up a password store on the first run
+                            { self.render_onboarding_page() }
+
+                            // the settings of the password store
+                            { self.render_store_settings_page() }
+
+                            // the application preferences
+                            { self.render_preferences_page() }
+                        </Stack>
+                    </Frame>
+                </Box>
+            </ScrolledWindow>
+        }
+    }
+}
+
+impl PasswordList {
+    fn render_error_bar(&self) -> VNode<Self> {
+        let (message, actions) = match self.model.error {
+            Some(ref error) => (error.to_string(), error.actions()),
+            None => (String::new(), vec![]),
+        };
+
+        gtk! {
+            <InfoBar
+                message_type=MessageType::Error
+                show_close_button=true
+                visible=self.model.error.is_some()
+                on response=|_, _| PasswordListMessage::DismissError
+            >
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    hexpand=true
+                >
+                    <Label
+                        label=message
+                        hexpand=true
+                        xalign=0.0
+                        line_wrap=true
+                        selectable=true
+                    />
+                    <Button
+                        label="Retry"
+                        valign=Align::Center
+                        visible=actions.contains(&ErrorAction::Retry)
+                        on clicked=|_| PasswordListMessage::Reload
+                    />
+                    <Button
+                        label="Choose another store"
+                        valign=Align::Center
+                        visible=actions.contains(&ErrorAction::ChooseStore)
+                        on clicked=|_| PasswordListMessage::ChooseStore
+                    />
+                    <Button
+                        label="Preferences"
+                        valign=Align::Center
+                        visible=actions.contains(&ErrorAction::OpenPreferences)
+                        on clicked=|_| PasswordListMessage::ShowPreferences
+                    />
+                </Box>
+            </InfoBar>
+        }
+    }
+
+    fn render_untrusted_commits_bar(&self) -> VNode<Self> {
+        const SHOWN_COMMITS: usize = 10;
+
+        let commits = &self.model.untrusted_commits;
+        let changes_recipients = commits
+            .iter()
+            .any(|commit| commit.files.iter().any(|file| file.ends_with(".gpg-id")));
+        let mut details = commits
+            .iter()
+            .take(SHOWN_COMMITS)
+            .map(|commit| {
+                let entries = commit.files
+                    .iter()
+                    .map(|file| {
+                        let entry = glib::markup_escape_text(file.trim_end_matches(".gpg")).to_string();
+                        if file.ends_with(".gpg-id") {
+                            format!("<b>{}</b>", entry)
+                        } else {
+                            entry
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!(
+                    "<tt>{}</tt> {} by {} ({}{})\n    {}",
+                    commit.short_hash(),
+                    glib::markup_escape_text(&commit.subject),
+                    glib::markup_escape_text(&commit.author),
+                    commit.status.description(),
+                    commit.key.as_ref().map(|key| format!(" {}", key)).unwrap_or_default(),
+                    entries,
+                )
+            })
+            .collect::<Vec<_>>();
+        if commits.len() > SHOWN_COMMITS {
+            details.push(format!("and {} more", commits.len() - SHOWN_COMMITS));
+        }
+
+        gtk! {
+            <InfoBar
+                message_type=if changes_recipients { MessageType::Error } else { MessageType::Warning }
+                show_close_button=true
+                visible=!commits.is_empty()
+                on response=|_, _| PasswordListMessage::DismissUntrustedCommits
+            >
+                <Box
+                    orientation=Orientation::Vertical
+                    spacing=4
+                    hexpand=true
+                >
+                    <Label
+                        markup=if changes_recipients {
+                            "<b>Untrusted commits changed the recipients of the store</b>"
+                        } else {
+                            "<b>The store contains commits without a trusted signature</b>"
+                        }
+                        xalign=0.0
+                    />
+                    <Label
+                        markup=details.join("\n")
+                        xalign=0.0
+                        line_wrap=true
+                        selectable=true
+                    />
+                    <Button
+                        label="Trust these commits"
+                        tooltip_text="Only trust the commits after verifying them with their authors"
+                        halign=Align::Start
+                        on clicked=|_| PasswordListMessage::TrustCommits
+                    />
+                </Box>
+            </InfoBar>
+        }
+    }
+
+    fn render_editor_page(&self) -> VNode<Self> {
+        let editor = match self.model.editor {
+            Some(ref editor) => editor,
+            None => return gtk! { <Box Stack::name="edit_password" /> },
+        };
+
+        if editor.review {
+            return self.render_review_page(editor);
+        }
+
+        gtk! {
+            <Box
+                Stack::name="edit_password"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Entry
+                    placeholder_text="Name of the entry, e.g. email/example.com"
+                    sensitive=editor.original.is_none()
+                    text=editor.path.clone()
+                    on changed=|entry| PasswordListMessage::SetEditorPath(entry.get_text().to_string())
+                />
+                <CheckButton
+                    label="Overwrite existing"
+                    tooltip_text="Replace an existing entry with the same name"
+                    visible=editor.original.is_none()
+                    active=editor.force
+                    on toggled=|check| PasswordListMessage::SetEditorForce(check.get_active())
+                />
+
+                // a widget for every parsed `key: value` field
+                {
+                    editor.content().fields().into_iter().map(|(key, value)| {
+                        gtk! {
+                            <Box
+                                orientation=Orientation::Horizontal
+                                spacing=16
+                            >
+                                <Label
+                                    label=key.to_owned()
+                                    xalign=0.0
+                                    width_chars=12
+                                    classes=vec!["dim-label".into()]
+                                />
+                                <Entry
+                                    hexpand=true
+                                    text=value.to_owned()
+                                    user_data_path=key.to_owned()
+                                    on changed=|entry| PasswordListMessage::SetEditorField(
+                                        entry.get_user_data_path(),
+                                        entry.get_text().to_string(),
+                                    )
+                                />
+                            </Box>
+                        }
+                    })
+                }
+
+                // the password generator replaces only the first line of the editor
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                >
+                    <Label label="Length" />
+                    <SpinButton::with_range(1.0, 256.0, 1.0)
+                        value=self.model.generator.length as f64
+                        on value_changed=|spin| PasswordListMessage::SetGeneratorLength(spin.get_value_as_int() as usize)
+                    />
+                    <CheckButton
+                        label="No symbols"
+                        active=self.model.generator.no_symbols
+                        on toggled=|check| PasswordListMessage::SetGeneratorNoSymbols(check.get_active())
+                    />
+                    <Button
+                        label="Generate"
+                        hexpand=true
+                        halign=Align::End
+                        on clicked=|_| PasswordListMessage::GeneratePassword
+                    />
+                </Box>
+
+                <Frame shadow_type=ShadowType::In>
+                    <TextView
+                        buffer=Some(&editor.buffer)
+                        monospace=true
+                        wrap_mode=WrapMode::WordChar
+                        height_request=160
+                    />
+                </Frame>
+                <Label
+                    label=editor.error.clone().unwrap_or_default()
+                    visible=editor.error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    halign=Align::End
+                >
+                    <Button
+                        label="Cancel"
+                        on clicked=|_| PasswordListMessage::CancelEdit
+                    />
+                    <Button
+                        label="Save"
+                        classes=vec!["suggested-action".into()]
+                        on clicked=|_| PasswordListMessage::SaveEntry
+                    />
+                </Box>
+            </Box>
+        }
+    }
+
+    fn render_review_page(&self, editor: &Editor) -> VNode<Self> {
+        gtk! {
+            <Box
+                Stack::name="edit_password"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup="<b>Review your changes</b>"
+                    xalign=0.0
+                />
+                { self.render_diff(&editor.diff()) }
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    halign=Align::End
+                >
+                    <Button
+                        label="Back to editor"
+                        on clicked=|_| PasswordListMessage::ReviewBack
+                    />
+                    <Button
+                        label="Save"
+                        classes=vec!["suggested-action".into()]
+                        on clicked=|_| PasswordListMessage::SaveEntry
+                    />
+                </Box>
+            </Box>
+        }
+    }
+
+    fn render_diff(&self, diff: &[DiffLine]) -> VNode<Self> {
+        let markup = diff.iter().map(|line| {
+            match line {
+                DiffLine::Unchanged(text) => format!("  {}", glib::markup_escape_text(text)),
+                DiffLine::Added(text) => format!(
+                    "<span foreground=\"#26a269\">+ {}</span>",
+                    glib::markup_escape_text(text),
+                ),
+                DiffLine::Removed(text) => format!(
+                    "<span foreground=\"#c01c28\">- {}</span>",
+                    glib::markup_escape_text(text),
+                ),
+            }
+        }).collect::<Vec<_>>().join("\n");
+
+        gtk! {
+            <Frame shadow_type=ShadowType::In>
+                <Label
+                    markup=format!("<tt>{}</tt>", markup)
+                    xalign=0.0
+                    margin_top=8
+                    margin_bottom=8
+                    margin_start=8
+                    margin_end=8
+                    selectable=true
+                />
+            </Frame>
+        }
+    }
+
+    fn render_transfer_page(&self) -> VNode<Self> {
+        let transfer = match self.model.transfer {
+            Some(ref transfer) => transfer,
+            None => return gtk! { <Box Stack::name="transfer" /> },
+        };
+
+        gtk! {
+            <Box
+                Stack::name="transfer"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup=format!(
+                        "<b>Move or copy “{}”</b>",
+                        glib::markup_escape_text(transfer.source.trim_start_matches("./")),
+                    )
+                    xalign=0.0
+                />
+                <Entry
+                    placeholder_text="New name, or a folder ending with /"
+                    text=transfer.destination.clone()
+                    on changed=|entry| PasswordListMessage::SetTransferDestination(entry.get_text().to_string())
+                />
+                <CheckButton
+                    label="Overwrite existing"
+                    active=transfer.force
+                    on toggled=|check| PasswordListMessage::SetTransferForce(check.get_active())
+                />
+                <Label
+                    label=transfer.error.clone().unwrap_or_default()
+                    visible=transfer.error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    halign=Align::End
+                >
+                    <Button
+                        label="Cancel"
+                        on clicked=|_| PasswordListMessage::Back
+                    />
+                    <Button
+                        label="Copy"
+                        on clicked=|_| PasswordListMessage::FinishTransfer { copy: true }
+                    />
+                    <Button
+                        label="Move"
+                        classes=vec!["suggested-action".into()]
+                        on clicked=|_| PasswordListMessage::FinishTransfer { copy: false }
+                    />
+                </Box>
+            </Box>
+        }
+    }
+
+    fn render_reencryption_page(&self) -> VNode<Self> {
+        let reencryption = match self.model.reencryption {
+            Some(ref reencryption) => reencryption,
+            None => return gtk! { <Box Stack::name="init_recipients" /> },
+        };
+        let folder = if reencryption.folder == "." {
+            "the password store".to_owned()
+        } else {
+            format!("“{}”", reencryption.folder.trim_start_matches("./"))
+        };
+
+        let content = match reencryption.state {
+            ReencryptionState::Editing => gtk! {
+                <Box
+                    orientation=Orientation::Vertical
+                    spacing=8
+                >
+                    <Label
+                        label="Enter the GPG ids of everyone who should be able to read the entries, separated by spaces. All entries are re-encrypted afterwards."
+                        xalign=0.0
+                        line_wrap=true
+                    />
+                    <Entry
+                        placeholder_text="alice@example.com 0x1234ABCD"
+                        text=reencryption.recipients_text.clone()
+                        on changed=|entry| PasswordListMessage::SetReencryptionRecipients(entry.get_text().to_string())
+                    />
+                    <Box
+                        orientation=Orientation::Horizontal
+                        spacing=8
+                        halign=Align::End
+                    >
+                        <Button
+                            label="Cancel"
+                            on clicked=|_| PasswordListMessage::CloseReencryption
+                        />
+                        <Button
+                            label="Re-encrypt"
+                            classes=vec!["suggested-action".into()]
+                            on clicked=|_| PasswordListMessage::BeginReencryption
+                        />
+                    </Box>
+                </Box>
+            },
+            ReencryptionState::Running => gtk! {
+                <Box
+                    orientation=Orientation::Vertical
+                    spacing=8
+                >
+                    <ProgressBar
+                        fraction=reencryption.progress()
+                        show_text=true
+                        text=format!(
+                            "{} of {}",
+                            reencryption.staged.len() + reencryption.failed.len(),
+                            reencryption.entries.len(),
+                        )
+                    />
+                    <Label
+                        label=reencryption.current_entry()
+                            .map(|entry| format!("Re-encrypting {}", entry.trim_start_matches("./")))
+                            .unwrap_or_else(|| "Writing the new recipients".to_owned())
+                        xalign=0.0
+                        ellipsize=EllipsizeMode::Middle
+                        classes=vec!["dim-label".into()]
+                    />
+                    <Button
+                        label="Cancel"
+                        halign=Align::End
+                        on clicked=|_| PasswordListMessage::CancelReencryption
+                    />
+                </Box>
+            },
+            ReencryptionState::Finished | ReencryptionState::Aborted => gtk! {
+                <Box
+                    orientation=Orientation::Vertical
+                    spacing=8
+                >
+                    <Label
+                        label=if reencryption.state == ReencryptionState::Finished {
+                            format!(
+                                "Re-encrypted {} entries to {}.{}",
+                                reencryption.staged.len(),
+                                reencryption.recipients.join(", "),
+                                reencryption.error
+                                    .as_ref()
+                                    .map(|error| format!(" The change could not be committed: {}", error))
+                                    .unwrap_or_default(),
+                            )
+                        } else {
+                            format!(
+                                "Nothing was changed. {}",
+                                reencryption.error.clone().unwrap_or_else(|| "The re-encryption was cancelled.".into()),
+                            )
+                        }
+                        xalign=0.0
+                        line_wrap=true
+                    />
+                    {
+                        reencryption.failed.iter().map(|(entry, error)| {
+                            gtk! {
+                                <Label
+                                    markup=format!(
+                                        "<b>{}</b>: {}",
+                                        glib::markup_escape_text(entry.trim_start_matches("./")),
+                                        glib::markup_escape_text(error),
+                                    )
+                                    xalign=0.0
+                                    line_wrap=true
+                                    classes=vec!["error".into()]
+                                />
+                            }
+                        })
+                    }
+                    <Button
+                        label="Close"
+                        halign=Align::End
+                        on clicked=|_| PasswordListMessage::CloseReencryption
+                    />
+                </Box>
+            },
+        };
+
+        gtk! {
+            <Box
+                Stack::name="init_recipients"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup=format!("<b>Recipients of {}</b>", glib::markup_escape_text(&folder))
+                    xalign=0.0
+                />
+                <Label
+                    label=reencryption.error.clone().unwrap_or_default()
+                    visible=reencryption.error.is_some() && reencryption.state == ReencryptionState::Editing
+                    xalign=0.0
+                    classes=vec!["error".into()]
+                />
+                { content }
+            </Box>
+        }
+    }
+
+    fn render_history_page(&self) -> VNode<Self> {
+        let history = match self.model.entry_history {
+            Some(ref history) => history,
+            None => return gtk! { <Box Stack::name="password_history" /> },
+        };
+
+        gtk! {
+            <Box
+                Stack::name="password_history"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup=format!(
+                        "<b>History of “{}”</b>",
+                        glib::markup_escape_text(history.password.trim_start_matches("./")),
+                    )
+                    xalign=0.0
+                />
+                <Frame shadow_type=ShadowType::In>
+                    <ListBox
+                        on row_activated=|_, row| PasswordListMessage::SelectRevision(row.get_user_data_path())
+                    >
+                        {
+                            history.commits.iter().map(|commit| {
+                                let selected = history.selected.as_ref() == Some(&commit.hash);
+                                gtk! {
+                                    <ListBoxRow
+                                        selectable=false
+                                        user_data_path=commit.hash.clone()
+                                    >
+                                        <Box
+                                            orientation=Orientation::Vertical
+                                            spacing=2
+                                            margin_top=4
+                                            margin_bottom=4
+                                            margin_start=8
+                                            margin_end=8
+                                        >
+                                            <Label
+                                                markup=if selected {
+                                                    format!("<b>{}</b>", glib::markup_escape_text(&commit.subject))
+                                                } else {
+                                                    glib::markup_escape_text(&commit.subject).to_string()
+                                                }
+                                                xalign=0.0
+                                                ellipsize=EllipsizeMode::End
+                                            />
+                                            <Label
+                                                markup=format!(
+                                                    "<small>{} · {} · {}</small>",
+                                                    commit.short_hash(),
+                                                    glib::markup_escape_text(&commit.author),
+                                                    commit.date,
+                                                )
+                                                xalign=0.0
+                                                classes=vec!["dim-label".into()]
+                                            />
+                                        </Box>
+                                    </ListBoxRow>
+                                }
+                            })
+                        }
+                    </ListBox>
+                </Frame>
+                <Label
+                    label=history.error.clone().unwrap_or_default()
+                    visible=history.error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                {
+                    if history.selected_content.is_some() {
+                        gtk! {
+                            <Box
+                                orientation=Orientation::Vertical
+                                spacing=8
+                            >
+                                <Label
+                                    label="Changes from the current content to the selected version"
+                                    xalign=0.0
+                                    classes=vec!["dim-label".into()]
+                                />
+                                { self.render_diff(&history.diff) }
+                                <Button
+                                    label="Restore this version"
+                                    halign=Align::End
+                                    classes=vec!["destructive-action".into()]
+                                    on clicked=|_| PasswordListMessage::RestoreRevision
+                                />
+                            </Box>
+                        }
+                    } else {
+                        gtk! { <Box /> }
+                    }
+                }
+            </Box>
+        }
+    }
+
+    fn render_merge_page(&self) -> VNode<Self> {
+        let merge = match self.model.merge {
+            Some(ref merge) => merge,
+            None => return gtk! { <Box Stack::name="merge_conflict" /> },
+        };
+        let versions = [
+            ("Base", merge.base.clone()),
+            (if merge.ours_label == "local" { "Local" } else { "Remote" }, merge.ours.clone()),
+            (if merge.theirs_label == "local" { "Local" } else { "Remote" }, merge.theirs.clone()),
+        ];
+
+        gtk! {
+            <Box
+                Stack::name="merge_conflict"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup=format!(
+                        "<b>Resolve the conflict of “{}”</b>",
+                        glib::markup_escape_text(merge.password.trim_start_matches("./")),
+                    )
+                    xalign=0.0
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    homogeneous=true
+                >
+                    {
+                        versions.iter().map(|(title, content)| {
+                            gtk! {
+                                <Box
+                                    orientation=Orientation::Vertical
+                                    spacing=4
+                                >
+                                    <Label
+                                        label=*title
+                                        xalign=0.0
+                                        classes=vec!["dim-label".into()]
+                                    />
+                                    <Frame shadow_type=ShadowType::In>
+                                        <Label
+                                            markup=match content {
+                                                Some(content) => format!("<tt>{}</tt>", glib::markup_escape_text(content)),
+                                                None => "<i>deleted</i>".to_owned(),
+                                            }
+                                            xalign=0.0
+                                            yalign=0.0
+                                            margin_top=4
+                                            margin_bottom=4
+                                            margin_start=4
+                                            margin_end=4
+                                            selectable=true
+                                        />
+                                    </Frame>
+                                </Box>
+                            }
+                        })
+                    }
+                </Box>
+                <Label
+                    label="Merged result"
+                    xalign=0.0
+                    classes=vec!["dim-label".into()]
+                />
+                <Frame shadow_type=ShadowType::In>
+                    <TextView
+                        buffer=Some(&merge.buffer)
+                        monospace=true
+                        wrap_mode=WrapMode::WordChar
+                        height_request=160
+                    />
+                </Frame>
+                <Label
+                    label=merge.deleted_by
+                        .as_ref()
+                        .map(|side| format!(
+                            "The entry was deleted by the {} version. Keep it deleted, or restore the version above.",
+                            side,
+                        ))
+                        .unwrap_or_default()
+                    visible=merge.deleted_by.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                />
+                <Label
+                    label=merge.error.iter().chain(&merge.save_error).cloned().collect::<Vec<_>>().join("\n")
+                    visible=merge.error.is_some() || merge.save_error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    halign=Align::End
+                >
+                    <Button
+                        label="Cancel"
+                        on clicked=|_| PasswordListMessage::Back
+                    />
+                    <Button
+                        label="Keep deleted"
+                        visible=merge.deleted_by.is_some()
+                        classes=vec!["destructive-action".into()]
+                        on clicked=|_| PasswordListMessage::KeepMergeDeleted
+                    />
+                    <Button
+                        label=if merge.deleted_by.is_some() { "Restore and commit" } else { "Save and commit" }
+                        sensitive=merge.error.is_none()
+                        classes=vec!["suggested-action".into()]
+                        on clicked=|_| PasswordListMessage::SaveMerge
+                    />
+                </Box>
+            </Box>
+        }
+    }
+
+    fn render_onboarding_page(&self) -> VNode<Self> {
+        let onboarding = match self.model.onboarding {
+            Some(ref onboarding) => onboarding,
+            None => return gtk! { <Box Stack::name="onboarding" /> },
+        };
+        let choices = [
+            (OnboardingChoice::Create, "Create", "Create a new password store encrypted for one of your keys"),
+            (OnboardingChoice::Clone, "Clone", "Clone an existing password store from a git repository"),
+            (OnboardingChoice::Existing, "Open", "Use an existing password store in another directory"),
+        ];
+        let (path_label, finish_label) = match onboarding.choice {
+            OnboardingChoice::Create => ("Directory of the new store", "Create store"),
+            OnboardingChoice::Clone => ("Clone into", "Clone store"),
+            OnboardingChoice::Existing => ("Directory of the store", "Open store"),
+        };
+
+        gtk! {
+            <Box
+                Stack::name="onboarding"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup="<big><b>Welcome</b></big>"
+                    xalign=0.0
+                />
+                <Label
+                    label=if self.model.password_store_path.is_dir() {
+                        "How do you want to set up the password store to use?".to_owned()
+                    } else if self.model.password_store_path.as_os_str().is_empty() {
+                        "There is no password store yet. How do you want to set it up?".to_owned()
+                    } else {
+                        format!(
+                            "There is no password store at {}. How do you want to set it up?",
+                            self.model.password_store_path.display(),
+                        )
+                    }
+                    xalign=0.0
+                    line_wrap=true
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    halign=Align::Start
+                    classes=vec!["linked".into()]
+                >
+                    {
+                        choices.iter().map(|&(choice, name, description)| {
+                            gtk! {
+                                <ToggleButton
+                                    label=name
+                                    tooltip_text=description
+                                    active=onboarding.choice == choice
+                                    user_data_path=choice.name().to_owned()
+                                    on clicked=|btn| PasswordListMessage::SetOnboardingChoice(
+                                        OnboardingChoice::from_name(&btn.get_user_data_path()).unwrap_or(OnboardingChoice::Create)
+                                    )
+                                />
+                            }
+                        })
+                    }
+                </Box>
+
+                // the repository to clone
+                <Label
+                    label="Repository path or URL"
+                    visible=onboarding.choice == OnboardingChoice::Clone
+                    xalign=0.0
+                    classes=vec!["dim-label".into()]
+                />
+                <Entry
+                    visible=onboarding.choice == OnboardingChoice::Clone
+                    text=onboarding.url.clone()
+                    placeholder_text="git@example.com:team/passwords.git"
+                    on changed=|entry| PasswordListMessage::SetOnboardingUrl(entry.get_text().to_string())
+                />
+
+                <Label
+                    label=path_label
+                    xalign=0.0
+                    classes=vec!["dim-label".into()]
+                />
+                <Entry
+                    text=onboarding.path.clone()
+                    on changed=|entry| PasswordListMessage::SetOnboardingPath(entry.get_text().to_string())
+                />
+
+                // the key a new store is encrypted for
+                <Label
+                    label="Encrypt for"
+                    visible=onboarding.choice == OnboardingChoice::Create
+                    xalign=0.0
+                    classes=vec!["dim-label".into()]
+                />
                 <Frame
-                    border_width=24
-                    shadow_type=ShadowType::EtchedIn
+                    visible=onboarding.choice == OnboardingChoice::Create
+                    shadow_type=ShadowType::In
                 >
-                    <Stack
-                        visible_child_name=self.model.visible_page()
-                        homogeneous=false
-                        transition_type=StackTransitionType::SlideLeftRight
+                    <ListBox
+                        on row_activated=|_, row| PasswordListMessage::SetOnboardingKey(row.get_user_data_path())
                     >
-                        // pages of the password list
                         {
-                            let root = self.model.passwords.root_node_id().unwrap();
-                            let entry = match self.model.passwords.get(root).unwrap().data() {
-                                Entry::Password(name) => name,
-                                Entry::Directory(name) => name,
-                            };
-                            self.render_password_list(root, entry.clone())
+                            onboarding.keys.iter().map(|key| {
+                                let selected = onboarding.key.as_ref() == Some(&key.fingerprint);
+                                gtk! {
+                                    <ListBoxRow
+                                        selectable=false
+                                        user_data_path=key.fingerprint.clone()
+                                    >
+                                        <Box
+                                            orientation=Orientation::Horizontal
+                                            spacing=8
+                                            margin_top=4
+                                            margin_bottom=4
+                                            margin_start=8
+                                            margin_end=8
+                                        >
+                                            <Image
+                                                property_icon_name="object-select-symbolic"
+                                                opacity=if selected { 1.0 } else { 0.0 }
+                                            />
+                                            <Box orientation=Orientation::Vertical>
+                                                <Label
+                                                    label=key.user_ids.first().cloned().unwrap_or_default()
+                                                    xalign=0.0
+                                                    ellipsize=EllipsizeMode::End
+                                                />
+                                                <Label
+                                                    markup=format!("<small><tt>{}</tt></small>", key.fingerprint)
+                                                    xalign=0.0
+                                                    classes=vec!["dim-label".into()]
+                                                />
+                                            </Box>
+                                        </Box>
+                                    </ListBoxRow>
+                                }
+                            })
                         }
+                    </ListBox>
+                </Frame>
+                <Label
+                    label="No secret keys found. Create a key or choose another encryption backend in the preferences."
+                    visible=onboarding.choice == OnboardingChoice::Create && onboarding.keys.is_empty()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["dim-label".into()]
+                />
+                <CheckButton
+                    label="Track changes with git"
+                    visible=onboarding.choice == OnboardingChoice::Create
+                    active=onboarding.use_git
+                    on toggled=|check| PasswordListMessage::SetOnboardingGit(check.get_active())
+                />
 
-                        // dummy page for now to show a password
-                        <Box
-                            orientation=Orientation::Vertical
-                            spacing=8 Stack::name="show_password"
-                        >
-                            <Label
-                                label=self.model.current_password.clone().unwrap_or("".into())
-                            />
+                <Label
+                    label=onboarding.error.clone().unwrap_or_default()
+                    visible=onboarding.error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                <Button
+                    label=finish_label
+                    halign=Align::End
+                    classes=vec!["suggested-action".into()]
+                    on clicked=|_| PasswordListMessage::FinishOnboarding
+                />
+            </Box>
+        }
+    }
+
+    fn render_qr_code_page(&self) -> VNode<Self> {
+        let (qr_view, content) = match (&self.model.qr_view, &self.model.current_content) {
+            (Some(qr_view), Some(content)) => (qr_view, content),
+            _ => return gtk! { <Box Stack::name="qr_code" /> },
+        };
+        let name = self.model.current_password
+            .as_ref()
+            .and_then(|password| password.split("/").last())
+            .unwrap_or("")
+            .to_owned();
+
+        let mut choices = vec![(QrContent::Password, "Password".to_owned())];
+        choices.extend(content.otp_uris().into_iter().enumerate().map(|(index, uri)| {
+            let name = Otp::parse(uri).map(|otp| otp.name()).unwrap_or_else(|_| (index + 1).to_string());
+            (QrContent::Otp(index), format!("OTP {}", name))
+        }));
+        if content.wifi().is_some() {
+            choices.push((QrContent::Wifi, "WiFi".to_owned()));
+        }
+
+        gtk! {
+            <Box
+                Stack::name="qr_code"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup=format!("<big><b>{}</b></big>", glib::markup_escape_text(&name))
+                    xalign=0.0
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    halign=Align::Start
+                    classes=vec!["linked".into()]
+                >
+                    {
+                        choices.into_iter().map(|(choice, label)| {
+                            gtk! {
+                                <ToggleButton
+                                    label=label
+                                    active=qr_view.content == choice
+                                    user_data_path=choice.name()
+                                    on clicked=|btn| PasswordListMessage::SetQrContent(
+                                        QrContent::from_name(&btn.get_user_data_path()).unwrap_or(QrContent::Password)
+                                    )
+                                />
+                            }
+                        })
+                    }
+                </Box>
+                <DrawingArea
+                    width_request=256
+                    height_request=256
+                    vexpand=true
+                    visible=qr_view.data.is_some()
+                    qr_code=qr_view.data.clone().unwrap_or_default()
+                />
+                <Label
+                    label=qr_view.error.clone().unwrap_or_default()
+                    visible=qr_view.error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                <Button
+                    label="Save as PNG/SVG…"
+                    tooltip_text="Save the QR code as an image, the format is chosen by the file extension"
+                    halign=Align::Start
+                    sensitive=qr_view.data.is_some()
+                    on clicked=|_| PasswordListMessage::SaveQrCode
+                />
+            </Box>
+        }
+    }
+
+    fn render_store_settings_page(&self) -> VNode<Self> {
+        let settings = match self.model.store_settings {
+            Some(ref settings) => settings,
+            None => return gtk! { <Box Stack::name="store_settings" /> },
+        };
+        let status = if !settings.has_git {
+            "The password store is not a git repository.".to_owned()
+        } else if settings.gpg_diff_problems.is_empty() {
+            "<tt>git log -p</tt> and <tt>git diff</tt> show decrypted changes of entries.".to_owned()
+        } else {
+            format!(
+                "<tt>git log -p</tt> cannot show decrypted changes of entries:\n{}",
+                settings.gpg_diff_problems
+                    .iter()
+                    .map(|problem| format!("• {}", glib::markup_escape_text(problem)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        };
+
+        gtk! {
+            <Box
+                Stack::name="store_settings"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup="<b>Git diffs of entries</b>"
+                    xalign=0.0
+                />
+                <Label
+                    markup=status
+                    xalign=0.0
+                    line_wrap=true
+                />
+                <Label
+                    label=settings.error.clone().unwrap_or_default()
+                    visible=settings.error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+                <Button
+                    label="Repair"
+                    tooltip_text="Add the gpg diff driver to .gitattributes and the repository configuration and commit the change"
+                    halign=Align::Start
+                    visible=settings.has_git && !settings.gpg_diff_problems.is_empty()
+                    on clicked=|_| PasswordListMessage::RepairGpgDiff
+                />
+            </Box>
+        }
+    }
+
+    fn render_preferences_page(&self) -> VNode<Self> {
+        let backends = [
+            (CryptoBackendKind::GpgBinary, "GnuPG", "Use the gpg binary and your GnuPG keyring"),
+            (CryptoBackendKind::Native, "Native", "Use the built-in OpenPGP implementation"),
+        ];
+
+        gtk! {
+            <Box
+                Stack::name="preferences"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup="<b>Encryption backend</b>"
+                    xalign=0.0
+                />
+                <Box
+                    orientation=Orientation::Horizontal
+                    classes=vec!["linked".into()]
+                >
+                    {
+                        backends.iter().map(|&(kind, name, description)| {
+                            gtk! {
+                                <ToggleButton
+                                    label=name
+                                    tooltip_text=description
+                                    active=self.model.config.crypto_backend == kind
+                                    user_data_path=kind.name().to_owned()
+                                    on clicked=|btn| PasswordListMessage::SetCryptoBackend(
+                                        CryptoBackendKind::from_name(&btn.get_user_data_path()).unwrap_or_default()
+                                    )
+                                />
+                            }
+                        })
+                    }
+                </Box>
+            </Box>
+        }
+    }
+
+    fn render_password_page(&self) -> VNode<Self> {
+        let name = self.model.current_password
+            .as_ref()
+            .and_then(|password| password.split("/").last())
+            .unwrap_or("")
+            .to_owned();
+        let has_conflict = self.model.current_password
+            .as_ref()
+            .map(|password| self.model.has_conflict(password))
+            .unwrap_or(false);
+
+        gtk! {
+            <Box
+                Stack::name="show_password"
+                orientation=Orientation::Vertical
+                spacing=8
+                margin_top=16
+                margin_bottom=16
+                margin_start=16
+                margin_end=16
+            >
+                <Label
+                    markup=format!("<big><b>{}</b></big>", glib::markup_escape_text(&name))
+                    xalign=0.0
+                />
+
+                // entries with a merge conflict show the version of one side only
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=8
+                    visible=has_conflict
+                >
+                    <Image property_icon_name="dialog-warning-symbolic" />
+                    <Label
+                        label="This entry has a merge conflict."
+                        hexpand=true
+                        xalign=0.0
+                    />
+                    <Button
+                        label="Resolve…"
+                        user_data_path=self.model.current_password.clone().unwrap_or_default()
+                        on clicked=|btn| PasswordListMessage::StartMerge(btn.get_user_data_path())
+                    />
+                </Box>
+                {
+                    if let Some(ref error) = self.model.password_error {
+                        gtk! {
                             <Label
-                                label="Not implemented yet"
+                                label=error.to_owned()
+                                xalign=0.0
+                                line_wrap=true
+                                selectable=true
+                                classes=vec!["error".into()]
                             />
-                        </Box>
-                    </Stack>
-                </Frame>
-            </ScrolledWindow>
+                        }
+                    } else if let Some(ref content) = self.model.current_content {
+                        self.render_password_content(content)
+                    } else {
+                        gtk! { <Box /> }
+                    }
+                }
+            </Box>
+        }
+    }
+
+    fn render_password_content(&self, content: &EntryContent) -> VNode<Self> {
+        let notes = content.notes();
+        let login = content.login().unwrap_or("").to_owned();
+        let url = content.url().unwrap_or("").to_owned();
+
+        gtk! {
+            <Box
+                orientation=Orientation::Vertical
+                spacing=8
+            >
+                // the browserpass-style `login:` and `url:` fields
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=16
+                    visible=!login.is_empty() || !url.is_empty()
+                >
+                    <Label
+                        label=login.clone()
+                        visible=!login.is_empty()
+                        xalign=0.0
+                        ellipsize=EllipsizeMode::End
+                        selectable=true
+                    />
+                    <LinkButton
+                        uri=url.clone()
+                        label=url.clone()
+                        visible=!url.is_empty()
+                        halign=Align::Start
+                    />
+                </Box>
+                <Box
+                    orientation=Orientation::Horizontal
+                    spacing=4
+                >
+                    <Entry
+                        hexpand=true
+                        editable=false
+                        visibility=self.model.password_visible
+                        text=content.password.clone()
+                    />
+                    <ToggleButton
+                        tooltip_text="Reveal password"
+                        active=self.model.password_visible
+                        image="view-reveal-symbolic"
+                        on toggled=|_| PasswordListMessage::TogglePasswordVisibility
+                    />
+                    <Button
+                        tooltip_text="Copy password"
+                        image="edit-copy-symbolic"
+                        user_data_path=self.model.current_password.clone().unwrap_or_default()
+                        on clicked=|btn| PasswordListMessage::CopyPassword(btn.get_user_data_path())
+                    />
+                    <Button
+                        tooltip_text="Generate a new password, keeping all other lines"
+                        image="view-refresh-symbolic"
+                        on clicked=|_| PasswordListMessage::RotatePassword
+                    />
+                    <Button
+                        tooltip_text="Edit entry"
+                        image="document-edit-symbolic"
+                        on clicked=|_| PasswordListMessage::EditEntry
+                    />
+                    <Button
+                        tooltip_text="Show as QR code"
+                        image="view-grid-symbolic"
+                        on clicked=|_| PasswordListMessage::ShowQrCode
+                    />
+                    <Button
+                        tooltip_text="Add OTP from image"
+                        image="camera-photo-symbolic"
+                        on clicked=|_| PasswordListMessage::AddOtpFromImage
+                    />
+                    <Button
+                        tooltip_text="History"
+                        image="document-open-recent-symbolic"
+                        on clicked=|_| PasswordListMessage::ShowEntryHistory
+                    />
+                    <Button
+                        tooltip_text="Move or copy entry"
+                        image="go-jump-symbolic"
+                        user_data_path=self.model.current_password.clone().unwrap_or_default()
+                        on clicked=|btn| PasswordListMessage::StartTransfer(btn.get_user_data_path())
+                    />
+                    <Button
+                        tooltip_text="Delete entry"
+                        image="user-trash-symbolic"
+                        user_data_path=self.model.current_password.clone().unwrap_or_default()
+                        on clicked=|btn| PasswordListMessage::DeleteEntry(btn.get_user_data_path())
+                    />
+                </Box>
+
+                // the one-time passwords of the entry
+                {
+                    self.model.otps().into_iter().enumerate().map(|(index, otp)| self.render_otp(index, otp))
+                }
+                <Label
+                    label=self.model.otp_error.clone().unwrap_or_default()
+                    visible=self.model.otp_error.is_some()
+                    xalign=0.0
+                    line_wrap=true
+                    classes=vec!["error".into()]
+                />
+
+                // the other `key: value` fields of the entry
+                {
+                    content.other_fields().into_iter().map(|(key, value)| {
+                        gtk! {
+                            <Box
+                                orientation=Orientation::Horizontal
+                                spacing=16
+                            >
+                                <Label
+                                    label=key.to_owned()
+                                    xalign=0.0
+                                    width_chars=12
+                                    classes=vec!["dim-label".into()]
+                                />
+                                <Label
+                                    label=value.to_owned()
+                                    xalign=0.0
+                                    hexpand=true
+                                    ellipsize=EllipsizeMode::End
+                                    selectable=true
+                                />
+                            </Box>
+                        }
+                    })
+                }
+
+                <Label
+                    label=notes.clone()
+                    visible=!notes.is_empty()
+                    xalign=0.0
+                    line_wrap=true
+                    selectable=true
+                />
+            </Box>
+        }
+    }
+
+    fn render_otp(&self, index: usize, otp: Result<Otp, OtpError>) -> VNode<Self> {
+        let otp = match otp {
+            Ok(otp) => otp,
+            Err(err) => return gtk! {
+                <Label
+                    label=err.to_string()
+                    xalign=0.0
+                    classes=vec!["error".into()]
+                />
+            },
+        };
+        let (code, remaining, period) = match (otp.kind, otp.totp(SystemTime::now())) {
+            (OtpKind::Totp { period }, Some((code, remaining))) => (code, remaining, period),
+            _ => return self.render_hotp(index, &otp),
+        };
+
+        gtk! {
+            <Box
+                orientation=Orientation::Horizontal
+                spacing=16
+            >
+                <Label
+                    label=otp.name()
+                    xalign=0.0
+                    width_chars=12
+                    ellipsize=EllipsizeMode::End
+                    classes=vec!["dim-label".into()]
+                />
+                <Label
+                    markup=format!("<big><tt>{}</tt></big>", code)
+                    xalign=0.0
+                    hexpand=true
+                    selectable=true
+                />
+                <DrawingArea
+                    width_request=20
+                    height_request=20
+                    valign=Align::Center
+                    tooltip_text=format!("The next code appears in {} seconds", remaining.as_secs() + 1)
+                    countdown_fraction=remaining.as_secs_f64() / period as f64
+                />
+                <Button
+                    tooltip_text="Copy code"
+                    image="edit-copy-symbolic"
+                    user_data_path=index.to_string()
+                    on clicked=|btn| PasswordListMessage::CopyOtp(btn.get_user_data_path().parse().unwrap_or_default())
+                />
+            </Box>
+        }
+    }
+
+    fn render_hotp(&self, index: usize, otp: &Otp) -> VNode<Self> {
+        let code = self.model.hotp_code
+            .as_ref()
+            .filter(|(hotp_index, _)| *hotp_index == index)
+            .map(|(_, code)| code.clone());
+
+        gtk! {
+            <Box
+                orientation=Orientation::Horizontal
+                spacing=16
+            >
+                <Label
+                    label=otp.name()
+                    xalign=0.0
+                    width_chars=12
+                    ellipsize=EllipsizeMode::End
+                    classes=vec!["dim-label".into()]
+                />
+                <Label
+                    markup=format!(
+                        "<big><tt>{}</tt></big>",
+                        code.clone().unwrap_or_else(|| "•".repeat(otp.digits as usize)),
+                    )
+                    xalign=0.0
+                    hexpand=true
+                    selectable=code.is_some()
+                />
+                <Button
+                    tooltip_text="Generate the next code, which increments and commits the counter"
+                    image="view-refresh-symbolic"
+                    user_data_path=index.to_string()
+                    on clicked=|btn| PasswordListMessage::GenerateHotp(btn.get_user_data_path().parse().unwrap_or_default())
+                />
+                <Button
+                    tooltip_text="Copy code"
+                    image="edit-copy-symbolic"
+                    sensitive=code.is_some()
+                    user_data_path=index.to_string()
+                    on clicked=|btn| PasswordListMessage::CopyOtp(btn.get_user_data_path().parse().unwrap_or_default())
+                />
+            </Box>
+        }
+    }
+
+    fn render_recipients_panel(&self) -> VNode<Self> {
+        let path = self.model.history.join("/");
+        let recipients = self.model.directory_recipients(&path);
+        let on_directory_page = self.model.is_active_page(&path);
+        let title = match recipients.source {
+            Some(ref source) if recipients.is_inherited_at(&path) => format!(
+                "Readable by {} recipient{} (from {})",
+                recipients.keys.len(),
+                if recipients.keys.len() == 1 { "" } else { "s" },
+                if source == "." { "/".to_owned() } else { source.trim_start_matches("./").to_owned() },
+            ),
+            Some(_) => format!(
+                "Readable by {} recipient{}",
+                recipients.keys.len(),
+                if recipients.keys.len() == 1 { "" } else { "s" },
+            ),
+            None => "This password store has no .gpg-id".to_owned(),
+        };
+
+        gtk! {
+            <Expander
+                visible=on_directory_page
+                margin_top=12
+                margin_start=24
+                margin_end=24
+                label=title
+            >
+                <Box
+                    orientation=Orientation::Vertical
+                    spacing=2
+                    margin_top=4
+                    margin_start=16
+                >
+                    {
+                        recipients.keys.iter().map(|key| {
+                            gtk! {
+                                <Label
+                                    label=key.to_owned()
+                                    xalign=0.0
+                                    selectable=true
+                                />
+                            }
+                        })
+                    }
+                    <Button
+                        label="Change recipients…"
+                        halign=Align::Start
+                        margin_top=4
+                        user_data_path=path.clone()
+                        on clicked=|btn| PasswordListMessage::StartReencryption(btn.get_user_data_path())
+                    />
+                </Box>
+            </Expander>
         }
     }
-}
 
-impl PasswordList {
     fn render_password_list(&self, node: &NodeId, path: String) -> Vec<VNode<Self>> {
         let password_list = self.model.passwords.children_ids(node).unwrap().flat_map(|child| {
-            if let Entry::Directory(child_name) = self.model.passwords.get(child).unwrap().data() {
+            if let Entry::Directory(child_name, _) = self.model.passwords.get(child).unwrap().data() {
                 self.render_password_list(child, format!("{}/{}", path, child_name))
             } else {
                 vec![]
@@ -202,7 +1810,9 @@ impl PasswordList {
                     let child = self.model.passwords.get(child).unwrap().data().clone();
                     match child {
                         Entry::Password(child_name) => self.render_password_entry(&path, child_name),
-                        Entry::Directory(child_name) => self.render_directory_entry(children, &path, &child_name),
+                        Entry::Directory(child_name, recipients) => {
+                            self.render_directory_entry(children, &path, &child_name, &recipients)
+                        },
                     }
                 })
             }
@@ -229,13 +1839,50 @@ impl PasswordList {
                         property_icon_name="dialog-password"
                         property_icon_size=3
                     />
-                    <Label label=child_name />
+                    <Label label=child_name.clone() />
+                    <Image
+                        property_icon_name="dialog-warning-symbolic"
+                        tooltip_text="This entry has a merge conflict"
+                        visible=self.model.has_conflict(&format!("{}/{}", path, child_name))
+                    />
+                    <Button
+                        hexpand=true
+                        halign=Align::End
+                        relief=ReliefStyle::None
+                        tooltip_text="Copy password"
+                        image="edit-copy-symbolic"
+                        user_data_path=format!("{}/{}", path, child_name)
+                        on clicked=|btn| PasswordListMessage::CopyPassword(btn.get_user_data_path())
+                    />
+                    <Button
+                        relief=ReliefStyle::None
+                        tooltip_text="Move or copy entry"
+                        image="go-jump-symbolic"
+                        user_data_path=format!("{}/{}", path, child_name)
+                        on clicked=|btn| PasswordListMessage::StartTransfer(btn.get_user_data_path())
+                    />
+                    <Button
+                        relief=ReliefStyle::None
+                        tooltip_text="Delete entry"
+                        image="user-trash-symbolic"
+                        user_data_path=format!("{}/{}", path, child_name)
+                        on clicked=|btn| PasswordListMessage::DeleteEntry(btn.get_user_data_path())
+                    />
                 </Box>
             </ListBoxRow>
         }
     }
 
-    fn render_directory_entry(&self, children: usize, path: &String, child_name: &String) -> VNode<Self> {
+    fn render_directory_entry(
+        &self,
+        children: usize,
+        path: &String,
+        child_name: &String,
+        recipients: &Recipients,
+    ) -> VNode<Self> {
+        let dir_path = format!("{}/{}", path, child_name);
+        let inherited = recipients.is_inherited_at(&dir_path);
+
         gtk! {
             <ListBoxRow
                 selectable=false
@@ -271,13 +1918,66 @@ impl PasswordList {
                             classes=vec!["dim-label".into()]
                         />
                     </Box>
-                    <Image
-                        property_icon_name="go-next-symbolic"
+
+                    // the recipients badge, highlighted if the folder has its own .gpg-id
+                    <Box
+                        orientation=Orientation::Horizontal
+                        spacing=4
                         hexpand=true
                         halign=Align::End
+                        tooltip_text=recipients_tooltip(recipients)
+                        classes=if inherited { vec!["dim-label".into()] } else { vec![] }
+                    >
+                        <Image property_icon_name="system-users-symbolic" />
+                        <Label
+                            markup=if inherited {
+                                format!("<small>{}</small>", recipients.keys.len())
+                            } else {
+                                format!("<small><b>{}</b></small>", recipients.keys.len())
+                            }
+                        />
+                    </Box>
+                    <Button
+                        relief=ReliefStyle::None
+                        tooltip_text="Move or copy folder"
+                        image="go-jump-symbolic"
+                        user_data_path=format!("{}/{}", path, child_name)
+                        on clicked=|btn| PasswordListMessage::StartTransfer(btn.get_user_data_path())
+                    />
+                    <Button
+                        relief=ReliefStyle::None
+                        tooltip_text="Delete folder"
+                        image="user-trash-symbolic"
+                        user_data_path=format!("{}/{}", path, child_name)
+                        on clicked=|btn| PasswordListMessage::DeleteEntry(btn.get_user_data_path())
+                    />
+                    <Image
+                        property_icon_name="go-next-symbolic"
                     />
                 </Box>
             </ListBoxRow>
         }
     }
 }
+
+fn recipients_tooltip(recipients: &Recipients) -> String {
+    if recipients.keys.is_empty() {
+        "No recipients".to_owned()
+    } else {
+        format!("Readable by:\n{}", recipients.keys.join("\n"))
+    }
+}
+
+fn format_elapsed(time: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+
+    match seconds {
+        0..=59 => "just now".to_owned(),
+        60..=3599 => format!("{} min ago", seconds / 60),
+        3600..=86399 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
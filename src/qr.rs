@@ -0,0 +1,82 @@
+use image::Luma;
+use qrcode::QrCode;
+use qrcode::render::svg;
+use rqrr::PreparedImage;
+
+use std::{fmt, fs};
+use std::path::Path;
+
+/// The minimum size of saved QR codes in pixels.
+const SAVED_SIZE: u32 = 512;
+
+#[derive(Clone, Debug)]
+pub enum QrError {
+    Image(String),
+    NotFound,
+    Decode(String),
+    Encode(String),
+    Save(String),
+}
+
+impl fmt::Display for QrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QrError::Image(msg) => write!(f, "Could not read the image: {}", msg),
+            QrError::NotFound => write!(f, "The image does not contain a QR code"),
+            QrError::Decode(msg) => write!(f, "Could not decode the QR code: {}", msg),
+            QrError::Encode(msg) => write!(f, "Could not create the QR code: {}", msg),
+            QrError::Save(msg) => write!(f, "Could not save the QR code: {}", msg),
+        }
+    }
+}
+
+impl From<image::ImageError> for QrError {
+    fn from(err: image::ImageError) -> Self {
+        QrError::Image(err.to_string())
+    }
+}
+
+/// The contents of all QR codes in the PNG or JPEG image at `path`. The image is decoded locally.
+pub fn decode_image(path: &Path) -> Result<Vec<String>, QrError> {
+    let mut image = PreparedImage::prepare(image::open(path)?.to_luma());
+    let grids = image.detect_grids();
+    if grids.is_empty() {
+        return Err(QrError::NotFound);
+    }
+
+    grids
+        .iter()
+        .map(|grid| {
+            grid.decode()
+                .map(|(_meta, content)| content)
+                .map_err(|err| QrError::Decode(err.to_string()))
+        })
+        .collect()
+}
+
+pub fn encode(data: &str) -> Result<QrCode, QrError> {
+    QrCode::new(data.as_bytes()).map_err(|err| QrError::Encode(err.to_string()))
+}
+
+/// Save the QR code of `data` as an SVG or PNG image, depending on the extension of `path`.
+pub fn save(data: &str, path: &Path) -> Result<(), QrError> {
+    let code = encode(data)?;
+    let is_svg = path
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("svg"))
+        .unwrap_or(false);
+
+    if is_svg {
+        let document = code
+            .render::<svg::Color>()
+            .min_dimensions(SAVED_SIZE, SAVED_SIZE)
+            .build();
+        fs::write(path, document).map_err(|err| QrError::Save(err.to_string()))
+    } else {
+        code.render::<Luma<u8>>()
+            .min_dimensions(SAVED_SIZE, SAVED_SIZE)
+            .build()
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|err| QrError::Save(err.to_string()))
+    }
+}